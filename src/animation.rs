@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use specs::prelude::*;
+use specs::shrev::{EventChannel, ReaderId};
+
+use crate::atlas::{Atlases, Clip};
+use crate::components::{self, Animation};
+
+// The ship's atlas and the clips the game switches between.
+pub const SHIP: &str = "ship";
+pub const IDLE: &str = "idle";
+pub const THRUST: &str = "thrust";
+pub const DEATH: &str = "death";
+// Clip every asteroid atlas needs.
+pub const SPIN: &str = "spin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum PlayMode {
+    // Starts over after the last frame.
+    Loop,
+    // Stops on the last frame and reports it is done.
+    Once,
+    // Runs to the last frame, back to the first and so on.
+    PingPong,
+}
+
+// Sent when a clip that plays once reaches the end of its last frame.
+#[derive(Debug, Clone)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+// Moves `animation` on by `delta_time` seconds. Returns true when that ran a
+// Once clip out.
+fn advance(animation: &mut Animation, clip: &Clip, delta_time: f64) -> bool {
+    if animation.finished {
+        return false;
+    }
+    let last = clip.frames.len() - 1;
+    // The data files may have changed since a save was made
+    animation.frame = animation.frame.min(last);
+    animation.elapsed += delta_time;
+
+    while animation.elapsed >= clip.frames[animation.frame].duration {
+        animation.elapsed -= clip.frames[animation.frame].duration;
+        match clip.mode {
+            PlayMode::Loop => animation.frame = (animation.frame + 1) % clip.frames.len(),
+            PlayMode::Once if animation.frame == last => {
+                animation.finished = true;
+                animation.elapsed = 0.0;
+                return true;
+            }
+            PlayMode::Once => animation.frame += 1,
+            PlayMode::PingPong if last == 0 => {}
+            PlayMode::PingPong => {
+                if animation.frame == last {
+                    animation.reversed = true;
+                } else if animation.frame == 0 {
+                    animation.reversed = false;
+                }
+                if animation.reversed {
+                    animation.frame -= 1;
+                } else {
+                    animation.frame += 1;
+                }
+            }
+        }
+    }
+    false
+}
+
+// Plays every Animation and shows its current frame's sprite through the
+// Renderable.
+pub struct Animator;
+
+impl<'a> System<'a> for Animator {
+    type SystemData = (
+        ReadExpect<'a, Atlases>,
+        Read<'a, components::DeltaTime>,
+        WriteStorage<'a, Animation>,
+        WriteStorage<'a, components::Renderable>,
+        Write<'a, EventChannel<AnimationFinished>>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (atlases, delta_time, mut playing, mut renderables, mut finished, entities) = data;
+
+        for (entity, animation, renderable) in (&entities, &mut playing, &mut renderables).join() {
+            let clip = match atlases.clip(&animation.set, &animation.clip) {
+                Some(clip) => clip,
+                None => continue,
+            };
+            if advance(animation, clip, delta_time.0) {
+                finished.single_write(AnimationFinished {
+                    entity,
+                    clip: animation.clip.clone(),
+                });
+            }
+            let sprite = &clip.frames[animation.frame].sprite;
+            if renderable.sprite != *sprite {
+                renderable.sprite.clone_from(sprite);
+            }
+        }
+    }
+}
+
+// Clears away what is left of a ship once its death clip has played.
+#[derive(Default)]
+pub struct WreckRemover {
+    reader: Option<ReaderId<AnimationFinished>>,
+}
+
+impl<'a> System<'a> for WreckRemover {
+    type SystemData = (Read<'a, EventChannel<AnimationFinished>>, Entities<'a>);
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<AnimationFinished>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (events, entities) = data;
+
+        for event in events.read(self.reader.as_mut().expect("WreckRemover was not set up")) {
+            if event.clip == DEATH {
+                entities.delete(event.entity).ok();
+            }
+        }
+    }
+}
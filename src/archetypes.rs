@@ -0,0 +1,144 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::animation::SPIN;
+use crate::atlas::Atlases;
+
+// Kind of the plain asteroids, and of everything in saves made before there
+// were other kinds.
+pub const ROCK: &str = "rock";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SpeedRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SplitDef {
+    pub size: u32,
+    pub count: u32,
+    pub spread: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AsteroidTier {
+    // The enemy type, tiers only split into tiers of their own kind.
+    pub kind: String,
+    pub size: u32,
+    // Atlas in atlases.ron that plays its spin clip.
+    pub atlas: String,
+    pub scale: u32,
+    pub hitbox_radius: f64,
+    pub speed: SpeedRange,
+    pub spin: f64,
+    pub score: u32,
+    pub splits_into: Option<SplitDef>,
+}
+
+impl AsteroidTier {
+    // How the tier is called in messages, like "rock 3".
+    pub fn name(&self) -> String {
+        format!("{} {}", self.kind, self.size)
+    }
+
+    pub fn random_speed<R: Rng>(&self, rng: &mut R) -> f64 {
+        if self.speed.min < self.speed.max {
+            rng.gen_range(self.speed.min..self.speed.max)
+        } else {
+            self.speed.min
+        }
+    }
+
+    // Headings of the pieces left behind when an asteroid moving along `heading`
+    // is destroyed, spread evenly and centered on the original direction.
+    pub fn split_headings(&self, heading: f64) -> Vec<f64> {
+        match self.splits_into {
+            None => Vec::new(),
+            Some(SplitDef { count: 1, .. }) => vec![heading],
+            Some(split) => (0..split.count)
+                .map(|i| {
+                    heading - split.spread / 2.0
+                        + split.spread * i as f64 / (split.count - 1) as f64
+                })
+                .collect(),
+        }
+    }
+}
+
+// Every asteroid tier the game knows about, keyed by `kind` and `size`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AsteroidArchetypes {
+    pub tiers: Vec<AsteroidTier>,
+}
+
+impl AsteroidArchetypes {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read asteroid archetypes {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let archetypes: AsteroidArchetypes = ron::from_str(text).map_err(|e| e.to_string())?;
+        archetypes.validate()?;
+        Ok(archetypes)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.tiers.is_empty() {
+            return Err("No asteroid tiers defined".to_string());
+        }
+        for (index, tier) in self.tiers.iter().enumerate() {
+            if self.tiers[..index]
+                .iter()
+                .any(|other| other.kind == tier.kind && other.size == tier.size)
+            {
+                return Err(format!("Asteroid tier {} is defined twice", tier.name()));
+            }
+            if tier.speed.min > tier.speed.max {
+                return Err(format!(
+                    "Asteroid tier {} has min speed above max",
+                    tier.name()
+                ));
+            }
+            if let Some(split) = tier.splits_into {
+                if self.tier(&tier.kind, split.size).is_none() {
+                    return Err(format!(
+                        "Asteroid tier {} splits into unknown tier {} {}",
+                        tier.name(),
+                        tier.kind,
+                        split.size
+                    ));
+                }
+                if split.size >= tier.size {
+                    return Err(format!(
+                        "Asteroid tier {} must split into a smaller tier",
+                        tier.name()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Every tier's atlas has to exist and have a spin clip.
+    pub fn check_atlases(&self, atlases: &Atlases) -> Result<(), String> {
+        for tier in self.tiers.iter() {
+            atlases
+                .check_clips(&tier.atlas, &[SPIN])
+                .map_err(|e| format!("Asteroid tier {}: {}", tier.name(), e))?;
+        }
+        Ok(())
+    }
+
+    pub fn tier(&self, kind: &str, size: u32) -> Option<&AsteroidTier> {
+        self.tiers
+            .iter()
+            .find(|tier| tier.kind == kind && tier.size == size)
+    }
+
+    pub fn random_tier<R: Rng>(&self, rng: &mut R) -> &AsteroidTier {
+        &self.tiers[rng.gen_range(0..self.tiers.len())]
+    }
+}
@@ -0,0 +1,200 @@
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+pub const ASSET_MANIFEST_PATH: &str = "assets/data/assets.ron";
+
+// Data files the game reads, assets.ron has to list all of them.
+pub const ASTEROIDS: &str = "asteroids";
+pub const ATLASES: &str = "atlases";
+pub const WAVES: &str = "waves";
+pub const PLAY_FIELD: &str = "play_field";
+pub const CAMERA: &str = "camera";
+pub const MOVEMENT: &str = "movement";
+pub const LIVES: &str = "lives";
+pub const PARTICLES: &str = "particles";
+pub const BINDINGS: &str = "bindings";
+pub const DISPLAY: &str = "display";
+const DATA_NAMES: [&str; 10] = [
+    ASTEROIDS, ATLASES, WAVES, PLAY_FIELD, CAMERA, MOVEMENT, LIVES, PARTICLES, BINDINGS, DISPLAY,
+];
+// Font every piece of text is written in.
+pub const UI_FONT: &str = "ui";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Texture,
+    Font,
+    Sound,
+    Data,
+}
+
+impl AssetKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            AssetKind::Texture => "texture",
+            AssetKind::Font => "font",
+            AssetKind::Sound => "sound",
+            AssetKind::Data => "data file",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FontDef {
+    pub path: String,
+    // Point size it is rendered at.
+    pub size: u16,
+}
+
+// Every file the game loads, under the name the code asks for it by. Paths are
+// relative to the game folder.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetManifest {
+    pub textures: BTreeMap<String, String>,
+    pub fonts: BTreeMap<String, FontDef>,
+    #[serde(default)]
+    pub sounds: BTreeMap<String, String>,
+    pub data: BTreeMap<String, String>,
+}
+
+impl AssetManifest {
+    // Reads the manifest and makes sure every file it lists is there.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read asset manifest {}: {}", path, e))?;
+        let manifest = Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))?;
+        manifest
+            .check_files()
+            .map_err(|e| format!("{}: {}", path, e))?;
+        Ok(manifest)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let manifest: AssetManifest = ron::from_str(text).map_err(|e| e.to_string())?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for name in DATA_NAMES {
+            if !self.data.contains_key(name) {
+                return Err(format!("Data file {:?} is missing", name));
+            }
+        }
+        if !self.fonts.contains_key(UI_FONT) {
+            return Err(format!("Font {:?} is missing", UI_FONT));
+        }
+        if let Some((name, _)) = self.fonts.iter().find(|(_, font)| font.size == 0) {
+            return Err(format!("Font {:?} needs a size above 0", name));
+        }
+        Ok(())
+    }
+
+    // Every listed file with its kind and name.
+    pub fn files(&self) -> impl Iterator<Item = (AssetKind, &str, &str)> + '_ {
+        fn entries(
+            kind: AssetKind,
+            files: &BTreeMap<String, String>,
+        ) -> impl Iterator<Item = (AssetKind, &str, &str)> + '_ {
+            files
+                .iter()
+                .map(move |(name, path)| (kind, name.as_str(), path.as_str()))
+        }
+        entries(AssetKind::Texture, &self.textures)
+            .chain(
+                self.fonts
+                    .iter()
+                    .map(|(name, font)| (AssetKind::Font, name.as_str(), font.path.as_str())),
+            )
+            .chain(entries(AssetKind::Sound, &self.sounds))
+            .chain(entries(AssetKind::Data, &self.data))
+    }
+
+    // Errors with every listed file that cannot be found, not just the first.
+    pub fn check_files(&self) -> Result<(), String> {
+        let missing: Vec<String> = self
+            .files()
+            .filter(|(_, _, path)| !Path::new(path).is_file())
+            .map(|(kind, name, path)| format!("{} {:?} at {}", kind.name(), name, path))
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Missing {}", missing.join(", ")))
+        }
+    }
+
+    pub fn data(&self, name: &str) -> Result<&str, String> {
+        self.data
+            .get(name)
+            .map(|path| path.as_str())
+            .ok_or(format!("No data file {:?} in the asset manifest", name))
+    }
+
+    pub fn font(&self, name: &str) -> Result<&FontDef, String> {
+        self.fonts
+            .get(name)
+            .ok_or(format!("No font {:?} in the asset manifest", name))
+    }
+}
+
+struct WatchedFile {
+    kind: AssetKind,
+    name: String,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+// Tells which files in the manifest were saved since it last looked, for
+// reloading assets while the game runs. Checking modification times now and
+// then keeps it to the standard library.
+pub struct AssetWatcher {
+    files: Vec<WatchedFile>,
+    interval: Duration,
+    last_check: Instant,
+}
+
+impl AssetWatcher {
+    pub fn new(manifest: &AssetManifest, interval: Duration) -> Self {
+        AssetWatcher {
+            files: manifest
+                .files()
+                .map(|(kind, name, path)| WatchedFile {
+                    kind,
+                    name: name.to_string(),
+                    path: PathBuf::from(path),
+                    modified: modified(Path::new(path)),
+                })
+                .collect(),
+            interval,
+            last_check: Instant::now(),
+        }
+    }
+
+    // Kind and name of every file changed since the last call, empty until
+    // `interval` has passed since the last check.
+    pub fn changed(&mut self) -> Vec<(AssetKind, String)> {
+        if self.last_check.elapsed() < self.interval {
+            return Vec::new();
+        }
+        self.last_check = Instant::now();
+        let mut changed = Vec::new();
+        for file in self.files.iter_mut() {
+            let now = modified(&file.path);
+            if now != file.modified {
+                file.modified = now;
+                changed.push((file.kind, file.name.clone()));
+            }
+        }
+        changed
+    }
+}
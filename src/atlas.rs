@@ -0,0 +1,179 @@
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+
+use crate::animation::{PlayMode, DEATH, IDLE, SHIP, THRUST};
+use crate::assets::AssetManifest;
+use crate::sheet::Sheet;
+
+// Atlas and sprite rockets are drawn with.
+pub const ROCKET: &str = "rocket";
+pub const ROCKET_SPRITE: &str = "rocket";
+
+// Part of an atlas image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct SpriteRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationFrame {
+    pub sprite: String,
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Clip {
+    pub mode: PlayMode,
+    pub frames: Vec<AnimationFrame>,
+}
+
+// One texture with the sprites cut out of it and the clips played from those.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Atlas {
+    // Name of the texture in the asset manifest.
+    pub texture: String,
+    // Name of a JSON sprite sheet in the asset manifest whose frames and frame
+    // tags are added to the sprites and clips listed here.
+    #[serde(default)]
+    pub sheet: Option<String>,
+    #[serde(default)]
+    pub sprites: BTreeMap<String, SpriteRect>,
+    #[serde(default)]
+    pub clips: BTreeMap<String, Clip>,
+}
+
+// Every atlas the game draws from, keyed by name.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Atlases {
+    pub atlases: BTreeMap<String, Atlas>,
+}
+
+impl Atlases {
+    // Reads the atlases along with the sprite sheets they name in `manifest`.
+    pub fn load(path: &str, manifest: &AssetManifest) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read atlases {}: {}", path, e))?;
+        let mut atlases: Atlases = ron::from_str(&text).map_err(|e| format!("{}: {}", path, e))?;
+        atlases.add_sheets(manifest)?;
+        atlases.validate().map_err(|e| format!("{}: {}", path, e))?;
+        Ok(atlases)
+    }
+
+    // Atlases that name a sprite sheet only get its sprites through `load`.
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let atlases: Atlases = ron::from_str(text).map_err(|e| e.to_string())?;
+        atlases.validate()?;
+        Ok(atlases)
+    }
+
+    fn add_sheets(&mut self, manifest: &AssetManifest) -> Result<(), String> {
+        for (atlas_name, atlas) in self.atlases.iter_mut() {
+            let Some(sheet_name) = &atlas.sheet else {
+                continue;
+            };
+            let sheet = Sheet::load(manifest.data(sheet_name)?)?;
+            for (name, sprite) in sheet.sprites {
+                if atlas.sprites.insert(name.clone(), sprite).is_some() {
+                    return Err(format!(
+                        "Sprite {}/{} is in sheet {:?} too",
+                        atlas_name, name, sheet_name
+                    ));
+                }
+            }
+            for (name, clip) in sheet.clips {
+                if atlas.clips.insert(name.clone(), clip).is_some() {
+                    return Err(format!(
+                        "Clip {}/{} is in sheet {:?} too",
+                        atlas_name, name, sheet_name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Names of the sprite sheets in the asset manifest the atlases read.
+    pub fn sheets(&self) -> impl Iterator<Item = &str> {
+        self.atlases
+            .values()
+            .filter_map(|atlas| atlas.sheet.as_deref())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (atlas_name, atlas) in self.atlases.iter() {
+            if atlas.sprites.is_empty() {
+                return Err(format!("Atlas {:?} has no sprites", atlas_name));
+            }
+            for (sprite_name, sprite) in atlas.sprites.iter() {
+                if sprite.width == 0 || sprite.height == 0 {
+                    return Err(format!("Sprite {}/{} is empty", atlas_name, sprite_name));
+                }
+            }
+            for (clip_name, clip) in atlas.clips.iter() {
+                if clip.frames.is_empty() {
+                    return Err(format!("Clip {}/{} has no frames", atlas_name, clip_name));
+                }
+                for frame in clip.frames.iter() {
+                    if frame.duration <= 0.0 {
+                        return Err(format!(
+                            "Clip {}/{} has a frame that does not last",
+                            atlas_name, clip_name
+                        ));
+                    }
+                    if !atlas.sprites.contains_key(&frame.sprite) {
+                        return Err(format!(
+                            "Clip {}/{} shows unknown sprite {:?}",
+                            atlas_name, clip_name, frame.sprite
+                        ));
+                    }
+                }
+            }
+        }
+        self.check_clips(SHIP, &[IDLE, THRUST, DEATH])?;
+        self.sprite(ROCKET, ROCKET_SPRITE)
+            .map(|_| ())
+            .ok_or(format!(
+                "Atlas {:?} has no {:?} sprite",
+                ROCKET, ROCKET_SPRITE
+            ))
+    }
+
+    // Every atlas has to be cut from a texture the manifest lists.
+    pub fn check_textures(&self, manifest: &AssetManifest) -> Result<(), String> {
+        for (name, atlas) in self.atlases.iter() {
+            if !manifest.textures.contains_key(&atlas.texture) {
+                return Err(format!(
+                    "Atlas {:?} uses texture {:?}, which the asset manifest does not list",
+                    name, atlas.texture
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Errors unless `atlas` exists and has all of `clips`.
+    pub fn check_clips(&self, atlas: &str, clips: &[&str]) -> Result<(), String> {
+        let found = self
+            .atlases
+            .get(atlas)
+            .ok_or(format!("Atlas {:?} is missing", atlas))?;
+        for clip in clips {
+            if !found.clips.contains_key(*clip) {
+                return Err(format!("Atlas {:?} has no {:?} clip", atlas, clip));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn sprite(&self, atlas: &str, sprite: &str) -> Option<SpriteRect> {
+        self.atlases.get(atlas)?.sprites.get(sprite).copied()
+    }
+
+    pub fn clip(&self, atlas: &str, clip: &str) -> Option<&Clip> {
+        self.atlases.get(atlas)?.clips.get(clip)
+    }
+}
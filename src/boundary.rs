@@ -0,0 +1,181 @@
+use serde::Deserialize;
+use specs::prelude::*;
+use specs::{Entities, Join};
+
+use crate::components::{self, Boundary};
+use crate::vector2D::Vector2D;
+
+// Size of the area the game is played in. Everything in the simulation happens
+// inside it, the camera decides how much of it is on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct PlayField {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for PlayField {
+    fn default() -> Self {
+        PlayField {
+            width: 800,
+            height: 600,
+        }
+    }
+}
+
+impl PlayField {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read play field {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let field: PlayField = ron::from_str(text).map_err(|e| e.to_string())?;
+        if field.width == 0 || field.height == 0 {
+            return Err("width and height must be above 0".to_string());
+        }
+        Ok(field)
+    }
+
+    pub fn size(&self) -> Vector2D {
+        Vector2D::new(self.width as f64, self.height as f64)
+    }
+
+    pub fn center(&self) -> Vector2D {
+        self.size().multiply(0.5)
+    }
+
+    // The same point moved onto the field, as a wrapping entity would be.
+    pub fn wrap(&self, point: Vector2D) -> Vector2D {
+        let size = self.size();
+        Vector2D::new(point.x.rem_euclid(size.x), point.y.rem_euclid(size.y))
+    }
+
+    // Shortest way from `from` to `to`. With `wrap` set the field is treated as
+    // a torus, so two entities on opposite edges are close to each other.
+    pub fn delta(&self, from: Vector2D, to: Vector2D, wrap: bool) -> Vector2D {
+        let mut delta = to.substract(from);
+        if wrap {
+            let size = self.size();
+            if delta.x > size.x / 2.0 {
+                delta.x -= size.x;
+            } else if delta.x < -size.x / 2.0 {
+                delta.x += size.x;
+            }
+            if delta.y > size.y / 2.0 {
+                delta.y -= size.y;
+            } else if delta.y < -size.y / 2.0 {
+                delta.y += size.y;
+            }
+        }
+        delta
+    }
+
+    // Offsets at which a wrapping entity has to be repeated so the part hanging
+    // over an edge shows up on the other side. Always contains the zero offset,
+    // and up to three more when the entity straddles an edge or a corner.
+    pub fn wrap_offsets(
+        &self,
+        center: Vector2D,
+        half_width: f64,
+        half_height: f64,
+    ) -> impl Iterator<Item = Vector2D> {
+        let size = self.size();
+        let x_offset = if center.x - half_width < 0.0 {
+            Some(size.x)
+        } else if center.x + half_width > size.x {
+            Some(-size.x)
+        } else {
+            None
+        };
+        let y_offset = if center.y - half_height < 0.0 {
+            Some(size.y)
+        } else if center.y + half_height > size.y {
+            Some(-size.y)
+        } else {
+            None
+        };
+
+        let x_offsets = std::iter::once(0.0).chain(x_offset);
+        std::iter::once(0.0)
+            .chain(y_offset)
+            .flat_map(move |y| x_offsets.clone().map(move |x| Vector2D::new(x, y)))
+    }
+}
+
+// Which side of `min..=max` the value is past, 1.0 above it, -1.0 below it and
+// 0.0 when inside.
+fn side(value: f64, min: f64, max: f64) -> f64 {
+    if value > max {
+        1.0
+    } else if value < min {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+// Applies every entity's Boundary once it has moved for the tick. The size used
+// for bouncing and despawning is the Collider radius, or a point without one.
+pub struct BoundaryEnforcer;
+
+impl<'a> System<'a> for BoundaryEnforcer {
+    type SystemData = (
+        WriteStorage<'a, components::Position>,
+        WriteStorage<'a, components::Velocity>,
+        ReadStorage<'a, Boundary>,
+        ReadStorage<'a, components::Collider>,
+        ReadExpect<'a, PlayField>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut positions, mut velocities, boundaries, colliders, field, entities) = data;
+        let size = field.size();
+
+        for (position, velocity, boundary, collider, entity) in (
+            &mut positions,
+            (&mut velocities).maybe(),
+            &boundaries,
+            colliders.maybe(),
+            &entities,
+        )
+            .join()
+        {
+            let radius = collider.map_or(0.0, |collider| collider.radius);
+            match boundary {
+                Boundary::Wrap => position.pos = field.wrap(position.pos),
+                Boundary::Bounce => {
+                    // Pushed back onto the field, and only turned around when
+                    // heading further out so one sitting on the edge and
+                    // already moving inwards keeps going.
+                    let min = Vector2D::new(radius, radius);
+                    let max = size.substract(min);
+                    let outside = Vector2D::new(
+                        side(position.pos.x, min.x, max.x),
+                        side(position.pos.y, min.y, max.y),
+                    );
+                    position.pos.x = position.pos.x.min(max.x).max(min.x);
+                    position.pos.y = position.pos.y.min(max.y).max(min.y);
+                    if let Some(velocity) = velocity {
+                        if outside.x * velocity.vel.x > 0.0 {
+                            velocity.vel.x = -velocity.vel.x;
+                        }
+                        if outside.y * velocity.vel.y > 0.0 {
+                            velocity.vel.y = -velocity.vel.y;
+                        }
+                    }
+                }
+                Boundary::Despawn => {
+                    if position.pos.x > size.x + radius
+                        || position.pos.x < -radius
+                        || position.pos.y > size.y + radius
+                        || position.pos.y < -radius
+                    {
+                        entities.delete(entity).ok();
+                    }
+                }
+            }
+        }
+    }
+}
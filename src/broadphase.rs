@@ -0,0 +1,116 @@
+use specs::prelude::*;
+use specs::{Entities, Join};
+
+use crate::boundary::PlayField;
+use crate::vector2D::Vector2D;
+use crate::{components, GRID_SIZE};
+
+// Uniform grid over the play field. Every collider is stored in each cell its
+// bounding box touches, so a big asteroid spanning several cells is still found
+// from any of them. Anything outside the field is clamped into the border cells,
+// wrapping colliders are inserted a second time on the far side instead.
+pub struct SpatialGrid {
+    cell_size: f64,
+    columns: i32,
+    rows: i32,
+    cells: Vec<Vec<Entity>>,
+}
+
+impl SpatialGrid {
+    pub fn new(width: i32, height: i32, cell_size: i32) -> Self {
+        let columns = (width + cell_size - 1) / cell_size;
+        let rows = (height + cell_size - 1) / cell_size;
+        SpatialGrid {
+            cell_size: cell_size as f64,
+            columns,
+            rows,
+            cells: vec![Vec::new(); (columns * rows) as usize],
+        }
+    }
+
+    pub fn for_field(field: &PlayField) -> Self {
+        SpatialGrid::new(field.width as i32, field.height as i32, GRID_SIZE)
+    }
+
+    // Empties every cell but keeps their allocations for the next rebuild.
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, center: Vector2D, radius: f64) {
+        let (min_x, max_x, min_y, max_y) = self.cell_range(center, radius);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let index = (y * self.columns + x) as usize;
+                self.cells[index].push(entity);
+            }
+        }
+    }
+
+    // Collects every entity sharing a cell with the given bounds into `found`,
+    // each entity only once.
+    pub fn query(&self, center: Vector2D, radius: f64, found: &mut Vec<Entity>) {
+        found.clear();
+        let (min_x, max_x, min_y, max_y) = self.cell_range(center, radius);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let index = (y * self.columns + x) as usize;
+                found.extend_from_slice(&self.cells[index]);
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+    }
+
+    fn cell_range(&self, center: Vector2D, radius: f64) -> (i32, i32, i32, i32) {
+        let to_column = |x: f64| ((x / self.cell_size).floor() as i32).clamp(0, self.columns - 1);
+        let to_row = |y: f64| ((y / self.cell_size).floor() as i32).clamp(0, self.rows - 1);
+        (
+            to_column(center.x - radius),
+            to_column(center.x + radius),
+            to_row(center.y - radius),
+            to_row(center.y + radius),
+        )
+    }
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::for_field(&PlayField::default())
+    }
+}
+
+// Rebuilds the grid from scratch every tick, after everything has moved. A
+// wrapping collider hanging over an edge is also inserted on the other side, so
+// queries there find it too.
+pub struct BroadphaseBuilder;
+
+impl<'a> System<'a> for BroadphaseBuilder {
+    type SystemData = (
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Collider>,
+        ReadStorage<'a, components::Boundary>,
+        Write<'a, SpatialGrid>,
+        ReadExpect<'a, PlayField>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (positions, colliders, boundaries, mut grid, field, entities) = data;
+
+        grid.clear();
+        for (position, collider, boundary, entity) in
+            (&positions, &colliders, boundaries.maybe(), &entities).join()
+        {
+            if boundary == Some(&components::Boundary::Wrap) {
+                for offset in field.wrap_offsets(position.pos, collider.radius, collider.radius) {
+                    grid.insert(entity, position.pos.add(offset), collider.radius);
+                }
+            } else {
+                grid.insert(entity, position.pos, collider.radius);
+            }
+        }
+    }
+}
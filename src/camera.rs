@@ -0,0 +1,216 @@
+use serde::Deserialize;
+use specs::{Join, World, WorldExt};
+
+use crate::boundary::PlayField;
+use crate::components;
+use crate::vector2D::Vector2D;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum CameraMode {
+    // Keeps the ship in the middle of the view wherever the field is bigger
+    // than the view.
+    Follow,
+    // Zooms so the whole field is on screen at once.
+    Fit,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CameraConfig {
+    pub view_width: u32,
+    pub view_height: u32,
+    pub mode: CameraMode,
+    pub zoom: f64,
+    pub shake_duration: f64,
+    pub hit_shake: f64,
+    pub death_shake: f64,
+}
+
+impl CameraConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read camera config {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let config: CameraConfig = ron::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.view_width == 0 || self.view_height == 0 {
+            return Err("view_width and view_height must be above 0".to_string());
+        }
+        if self.zoom <= 0.0 {
+            return Err("zoom must be above 0".to_string());
+        }
+        if self.shake_duration < 0.0 || self.hit_shake < 0.0 || self.death_shake < 0.0 {
+            return Err("shake_duration, hit_shake and death_shake cannot be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Which part of the world is on screen. Screen coordinates are in the view's
+// own pixels, the front-end scales those to the window.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    // World point in the middle of the view.
+    pub position: Vector2D,
+    // Where it was at the start of the tick, frames between ticks blend the two.
+    previous_position: Vector2D,
+    // Screen pixels per world pixel.
+    pub zoom: f64,
+    view: Vector2D,
+    mode: CameraMode,
+    shake_duration: f64,
+    shake_strength: f64,
+    shake_remaining: f64,
+    // Time since the shake started, picks the direction it pushes in.
+    shake_time: f64,
+}
+
+impl Camera {
+    pub fn new(config: &CameraConfig, field: &PlayField) -> Self {
+        let view = Vector2D::new(config.view_width as f64, config.view_height as f64);
+        let zoom = match config.mode {
+            CameraMode::Follow => config.zoom,
+            CameraMode::Fit => (view.x / field.width as f64).min(view.y / field.height as f64),
+        };
+        Camera {
+            position: field.center(),
+            previous_position: field.center(),
+            zoom,
+            view,
+            mode: config.mode,
+            shake_duration: config.shake_duration,
+            shake_strength: 0.0,
+            shake_remaining: 0.0,
+            shake_time: 0.0,
+        }
+    }
+
+    pub fn view_size(&self) -> (u32, u32) {
+        (self.view.x as u32, self.view.y as u32)
+    }
+
+    pub fn shows_whole_field(&self, field: &PlayField) -> bool {
+        let size = field.size().multiply(self.zoom);
+        size.x <= self.view.x && size.y <= self.view.y
+    }
+
+    // Moves the view onto `target` along each axis the field does not fit on,
+    // along the others it stays centered on the field. Without a target, as
+    // while waiting for a new ship, the view stays where it was.
+    pub fn follow(&mut self, field: &PlayField, target: Option<Vector2D>) {
+        self.previous_position = self.position;
+        let center = field.center();
+        let target = match self.mode {
+            CameraMode::Follow => target.map_or(self.position, |target| field.wrap(target)),
+            CameraMode::Fit => center,
+        };
+        let size = field.size().multiply(self.zoom);
+        self.position = Vector2D::new(
+            if size.x > self.view.x {
+                target.x
+            } else {
+                center.x
+            },
+            if size.y > self.view.y {
+                target.y
+            } else {
+                center.y
+            },
+        );
+    }
+
+    // Stops the next frames easing in from the previous position, for when the
+    // view jumps somewhere new.
+    pub fn settle(&mut self) {
+        self.previous_position = self.position;
+    }
+
+    // Starts a shake `strength` screen pixels wide, unless a stronger one is
+    // already going.
+    pub fn shake(&mut self, strength: f64) {
+        if strength > self.current_shake() {
+            self.shake_strength = strength;
+            self.shake_remaining = self.shake_duration;
+            self.shake_time = 0.0;
+        }
+    }
+
+    pub fn update_shake(&mut self, delta_time: f64) {
+        self.shake_remaining = (self.shake_remaining - delta_time).max(0.0);
+        self.shake_time += delta_time;
+    }
+
+    // Shakes fade out over their duration.
+    fn current_shake(&self) -> f64 {
+        if self.shake_duration <= 0.0 {
+            return 0.0;
+        }
+        self.shake_strength * self.shake_remaining / self.shake_duration
+    }
+
+    // How far shaking pushes the picture, in screen pixels.
+    pub fn shake_offset(&self) -> Vector2D {
+        Vector2D::new(
+            (self.shake_time * 67.0).sin(),
+            (self.shake_time * 53.0).cos(),
+        )
+        .multiply(self.current_shake())
+    }
+
+    // The middle of the view for a frame `alpha` of the way to the next tick.
+    pub fn interpolated_position(&self, field: &PlayField, alpha: f64) -> Vector2D {
+        let delta = field.delta(self.previous_position, self.position, true);
+        self.previous_position.add(delta.multiply(alpha))
+    }
+
+    fn screen_center(&self) -> Vector2D {
+        self.view.multiply(0.5)
+    }
+
+    // Where `point` is drawn when the middle of the view is at `center`.
+    pub fn world_to_screen(&self, center: Vector2D, point: Vector2D) -> Vector2D {
+        point
+            .substract(center)
+            .multiply(self.zoom)
+            .add(self.screen_center())
+            .add(self.shake_offset())
+    }
+
+    // The world point under `point` on screen. Shaking is left out so the aim
+    // does not wobble along with it.
+    pub fn screen_to_world(&self, point: Vector2D) -> Vector2D {
+        point
+            .substract(self.screen_center())
+            .multiply(1.0 / self.zoom)
+            .add(self.position)
+    }
+}
+
+// Points the camera at the ship for this tick and lets shaking die down.
+pub fn update_camera(ecs: &World) {
+    let delta_time = ecs.read_resource::<components::DeltaTime>().0;
+    let field = ecs.read_resource::<PlayField>();
+    let players = ecs.read_storage::<components::Player>();
+    let positions = ecs.read_storage::<components::Position>();
+    let target = (&players, &positions)
+        .join()
+        .map(|(_, position)| position.pos)
+        .next();
+
+    let mut camera = ecs.write_resource::<Camera>();
+    camera.follow(&field, target);
+    camera.update_shake(delta_time);
+}
+
+// Puts the camera straight on the ship, for a world that was just built or
+// restored.
+pub fn snap_to_player(ecs: &World) {
+    update_camera(ecs);
+    ecs.write_resource::<Camera>().settle();
+}
@@ -0,0 +1,124 @@
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+use specs::{Entities, Join};
+
+use crate::boundary::PlayField;
+use crate::broadphase::SpatialGrid;
+use crate::components;
+use crate::vector2D::Vector2D;
+
+// Published by CollisionDetector every tick. Detection never changes the world
+// itself, whatever should happen on a hit is up to the systems reading these.
+#[derive(Debug, Clone, Copy)]
+pub enum CollisionEvent {
+    RocketHitAsteroid {
+        rocket: Entity,
+        asteroid: Entity,
+        position: Vector2D,
+    },
+    PlayerHitAsteroid {
+        player: Entity,
+        asteroid: Entity,
+        position: Vector2D,
+    },
+}
+
+// Colliders of the two entities overlap. When either one wraps, the distance is
+// measured across the edges too, so hits on something straddling one count.
+fn touching(
+    entity: Entity,
+    other: Entity,
+    positions: &ReadStorage<components::Position>,
+    colliders: &ReadStorage<components::Collider>,
+    boundaries: &ReadStorage<components::Boundary>,
+    field: &PlayField,
+) -> bool {
+    match (
+        positions.get(entity),
+        colliders.get(entity),
+        positions.get(other),
+        colliders.get(other),
+    ) {
+        (Some(position), Some(collider), Some(other_position), Some(other_collider)) => {
+            let wrap = boundaries.get(entity) == Some(&components::Boundary::Wrap)
+                || boundaries.get(other) == Some(&components::Boundary::Wrap);
+            field
+                .delta(position.pos, other_position.pos, wrap)
+                .magnitude()
+                < collider.radius + other_collider.radius
+        }
+        _ => false,
+    }
+}
+
+pub struct CollisionDetector;
+
+impl<'a> System<'a> for CollisionDetector {
+    type SystemData = (
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Rocket>,
+        ReadStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::Collider>,
+        ReadStorage<'a, components::Boundary>,
+        Read<'a, SpatialGrid>,
+        ReadExpect<'a, PlayField>,
+        Write<'a, EventChannel<CollisionEvent>>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            positions,
+            players,
+            rockets,
+            asteroids,
+            colliders,
+            boundaries,
+            grid,
+            field,
+            mut events,
+            entities,
+        ) = data;
+        let mut nearby = Vec::new();
+
+        for (player_pos, _, player_collider, player) in
+            (&positions, &players, &colliders, &entities).join()
+        {
+            grid.query(player_pos.pos, player_collider.radius, &mut nearby);
+            let hit = nearby.iter().find(|&&other| {
+                asteroids.contains(other)
+                    && touching(player, other, &positions, &colliders, &boundaries, &field)
+            });
+            if let Some(&asteroid) = hit {
+                events.single_write(CollisionEvent::PlayerHitAsteroid {
+                    player,
+                    asteroid,
+                    position: player_pos.pos,
+                });
+            }
+        }
+
+        // A rocket is spent on the first asteroid it touches and an asteroid can
+        // only be destroyed once, even when several rockets reach it together.
+        let mut hit_asteroids = BitSet::new();
+        for (rocket_pos, _, rocket_collider, rocket) in
+            (&positions, &rockets, &colliders, &entities).join()
+        {
+            grid.query(rocket_pos.pos, rocket_collider.radius, &mut nearby);
+            let hit = nearby.iter().find(|&&other| {
+                asteroids.contains(other)
+                    && !hit_asteroids.contains(other.id())
+                    && touching(rocket, other, &positions, &colliders, &boundaries, &field)
+            });
+            if let Some(&asteroid) = hit {
+                hit_asteroids.add(asteroid.id());
+                events.single_write(CollisionEvent::RocketHitAsteroid {
+                    rocket,
+                    asteroid,
+                    position: rocket_pos.pos,
+                });
+            }
+        }
+    }
+}
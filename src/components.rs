@@ -1,62 +1,145 @@
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs_derive::Component;
 
-use sdl2::rect::Point;
+use crate::vector2D::Vector2D;
 
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Position {
-    pub pos: Point,
+    pub pos: Vector2D,
     pub rot: f64,
 }
-#[derive(Component)]
+// Position at the start of the current tick, the renderer blends between this
+// and Position so movement stays smooth when frames and ticks do not line up.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct PreviousPosition {
+    pub pos: Vector2D,
+}
+// Movement in pixels per second, integrated into Position every tick.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Velocity {
+    pub vel: Vector2D,
+}
+// A sprite from atlases.ron drawn at Position. Entities with an Animation get
+// the sprite set to their current frame every tick.
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Renderable {
-    pub texture_name: String,
-    pub input_width: u32,
-    pub input_height: u32,
+    // Missing from saves before version 5, restoring works them out instead.
+    #[serde(default)]
+    pub atlas: String,
+    #[serde(default)]
+    pub sprite: String,
+    // Size in world pixels, the sprite is stretched to fit.
     pub output_width: u32,
     pub output_height: u32,
-    pub frame: u32,
-    pub total_frames: u32,
     pub render_rotation: f64,
+    // Missing from saves before version 3, restoring works it out instead.
+    #[serde(default)]
+    pub layer: RenderLayer,
+}
+// Plays `clip` from the atlas named `set`, see Animator.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Animation {
+    pub set: String,
+    pub clip: String,
+    pub frame: usize,
+    // Seconds spent on the current frame so far.
+    pub elapsed: f64,
+    // Going from the last frame back to the first in a PingPong clip.
+    pub reversed: bool,
+    // A Once clip that has played out, it stays on its last frame.
+    pub finished: bool,
+}
+
+impl Animation {
+    pub fn new(set: &str, clip: &str) -> Self {
+        Animation {
+            set: set.to_string(),
+            clip: clip.to_string(),
+            frame: 0,
+            elapsed: 0.0,
+            reversed: false,
+            finished: false,
+        }
+    }
+
+    // Switches to `clip` from its start, unless it is already playing.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            *self = Animation::new(&self.set, clip);
+        }
+    }
+}
+
+// Draw order, each layer is drawn over the ones before it. Inside a layer
+// things are drawn in the order the renderer collects them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RenderLayer {
+    Background,
+    #[default]
+    Asteroids,
+    Projectiles,
+    Player,
+    Effects,
+    Hud,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Player {
-    pub speed: i32,
-    pub direction: Direction,
     pub can_take_damage: bool,
 }
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Asteroid {
-    pub speed: f64,
     pub rotation_speed: f64,
-    pub size_multiplier: u32,
+    // Together the kind and size pick the tier in asteroids.ron.
+    // Missing from saves before version 6, restoring works it out instead.
+    #[serde(default)]
+    pub kind: String,
+    pub size: u32,
 }
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Rocket {
     pub speed: f64,
 }
 
-pub struct PendingAsteroid {
-    pub position: Point,
-    pub rot: f64,
-    pub size_mult: u32,
-}
-
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct GameData {
     pub score: u32,
     pub level: u32,
+    // Ships left, including the one currently flying.
+    pub lives: u32,
+    pub extra_lives_awarded: u32,
+    // Seconds until the next ship appears, set while the player is dead.
+    pub respawn_timer: Option<f64>,
 }
-#[derive(Component)]
+// Circular hit area around Position, also used as the bounds when the
+// entity is placed in the broadphase grid.
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Collider {
-    pub grid_x: i32,
-    pub grid_y: i32,
+    pub radius: f64,
+}
+
+// Nothing can hurt the entity until `remaining` seconds have passed.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Invulnerable {
+    pub remaining: f64,
+}
+
+// What happens when an entity reaches the edge of the play field.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Boundary {
+    // Leaves one side and comes back in on the opposite one.
+    Wrap,
+    // Reflects off the edge.
+    Bounce,
+    // Is deleted once it is completely off the field.
+    Despawn,
 }
+
+// Length of a simulation tick in seconds.
+#[derive(Default)]
+pub struct DeltaTime(pub f64);
+
+// Every random number in the simulation comes from here. It is seeded once per
+// run, so the same seed and input play out the same way.
+pub type GameRng = rand_chacha::ChaCha8Rng;
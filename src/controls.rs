@@ -0,0 +1,134 @@
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::GameControllerSubsystem;
+
+use std::collections::HashMap;
+
+use asteroidgame::input::{Action, Bindings, InputEvent, PadAxis};
+use asteroidgame::vector2D::Vector2D;
+
+// Makes sure every key and button name in the bindings is one SDL knows, so a
+// typo shows up at startup instead of as a control that silently does nothing.
+pub fn check_bindings(bindings: &Bindings) -> Result<(), String> {
+    if let Some(key_name) = bindings
+        .keys
+        .keys()
+        .find(|key_name| Keycode::from_name(key_name).is_none())
+    {
+        return Err(format!("Unknown key name {:?} in bindings", key_name));
+    }
+    if let Some(button_name) = bindings
+        .buttons
+        .keys()
+        .find(|button_name| Button::from_string(button_name).is_none())
+    {
+        return Err(format!("Unknown button name {:?} in bindings", button_name));
+    }
+    Ok(())
+}
+
+fn pad_axis(axis: Axis) -> PadAxis {
+    match axis {
+        Axis::LeftX => PadAxis::LeftX,
+        Axis::LeftY => PadAxis::LeftY,
+        Axis::RightX => PadAxis::RightX,
+        Axis::RightY => PadAxis::RightY,
+        Axis::TriggerLeft => PadAxis::LeftTrigger,
+        Axis::TriggerRight => PadAxis::RightTrigger,
+    }
+}
+
+// Turns an SDL event into input for the simulation, None when it is not bound
+// to anything.
+pub fn translate(event: &Event, bindings: &Bindings) -> Option<InputEvent> {
+    match event {
+        Event::KeyDown {
+            keycode: Some(key),
+            repeat: false,
+            ..
+        } => bindings.action_for_key(&key.name()).map(InputEvent::Down),
+        Event::KeyUp {
+            keycode: Some(key), ..
+        } => bindings.action_for_key(&key.name()).map(InputEvent::Up),
+        Event::MouseMotion { x, y, .. } => {
+            Some(InputEvent::AimAt(Vector2D::new(*x as f64, *y as f64)))
+        }
+        Event::ControllerButtonDown { which, button, .. } => bindings
+            .action_for_button(&button.string())
+            .map(|action| InputEvent::PadDown(*which, action)),
+        Event::ControllerButtonUp { which, button, .. } => bindings
+            .action_for_button(&button.string())
+            .map(|action| InputEvent::PadUp(*which, action)),
+        Event::ControllerAxisMotion {
+            which, axis, value, ..
+        } => {
+            let value = (*value as f64 / i16::MAX as f64).clamp(-1.0, 1.0);
+            Some(InputEvent::PadAxis(*which, pad_axis(*axis), value))
+        }
+        Event::ControllerDeviceRemoved { which, .. } => Some(InputEvent::PadDisconnected(*which)),
+        _ => None,
+    }
+}
+
+// The action a key or button press starts, ignoring key repeat.
+pub fn pressed_action(event: &Event, bindings: &Bindings) -> Option<Action> {
+    translate(event, bindings).and_then(InputEvent::pressed)
+}
+
+// Menus are driven by Enter, Escape and the arrow keys, a controller's A and B
+// buttons and d-pad stand in for them.
+pub fn menu_key(event: &Event) -> Option<Keycode> {
+    match event {
+        Event::KeyDown {
+            keycode: Some(key),
+            repeat: false,
+            ..
+        } => Some(*key),
+        Event::ControllerButtonDown { button, .. } => match button {
+            Button::A | Button::Start => Some(Keycode::Return),
+            Button::B | Button::Back => Some(Keycode::Escape),
+            Button::DPadUp => Some(Keycode::Up),
+            Button::DPadDown => Some(Keycode::Down),
+            Button::DPadLeft => Some(Keycode::Left),
+            Button::DPadRight => Some(Keycode::Right),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Keeps every plugged in controller open. SDL reports controllers that are
+// already connected at startup as added too, so there is no separate scan.
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    controllers: HashMap<u32, GameController>,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        Gamepads {
+            subsystem,
+            controllers: HashMap::new(),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::ControllerDeviceAdded { which, .. } => match self.subsystem.open(*which) {
+                Ok(controller) => {
+                    println!("Controller connected: {}", controller.name());
+                    self.controllers
+                        .insert(controller.instance_id(), controller);
+                }
+                Err(e) => println!("Could not open controller {}: {}", which, e),
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                if let Some(controller) = self.controllers.remove(which) {
+                    println!("Controller disconnected: {}", controller.name());
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use sdl2::render::WindowCanvas;
+use sdl2::VideoSubsystem;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum WindowMode {
+    // A normal window that can be moved and resized.
+    Windowed,
+    // A window without decorations, centered on the screen.
+    Borderless,
+    // Takes over the display at the configured resolution.
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "windowed" => Some(WindowMode::Windowed),
+            "borderless" => Some(WindowMode::Borderless),
+            "fullscreen" => Some(WindowMode::Fullscreen),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DisplaySettings {
+    pub width: u32,
+    pub height: u32,
+    pub mode: WindowMode,
+    pub vsync: bool,
+}
+
+impl DisplaySettings {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read display settings {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let settings: DisplaySettings = ron::from_str(text).map_err(|e| e.to_string())?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err("Window width and height must be above 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Reads a resolution written as WIDTHxHEIGHT, as in `1280x720`.
+pub fn parse_resolution(text: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("Invalid resolution {:?}, use WIDTHxHEIGHT", text);
+    let (width, height) = text.split_once('x').ok_or_else(invalid)?;
+    let width = width.parse::<u32>().map_err(|_| invalid())?;
+    let height = height.parse::<u32>().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width, height))
+}
+
+// Opens the window and sets the canvas up to draw in a `view_width` by
+// `view_height` area. SDL scales that to the window, keeping the aspect ratio, and maps mouse
+// positions back so aiming works at any size.
+pub fn create_canvas(
+    video_subsystem: &VideoSubsystem,
+    settings: &DisplaySettings,
+    view_width: u32,
+    view_height: u32,
+) -> Result<WindowCanvas, String> {
+    let mut window_builder = video_subsystem.window("Astroids", settings.width, settings.height);
+    match settings.mode {
+        WindowMode::Windowed => window_builder.position_centered().resizable(),
+        WindowMode::Borderless => window_builder.position_centered().borderless(),
+        WindowMode::Fullscreen => window_builder.fullscreen(),
+    };
+    let window = window_builder.build().map_err(|e| e.to_string())?;
+
+    let mut canvas_builder = window.into_canvas();
+    if settings.vsync {
+        canvas_builder = canvas_builder.present_vsync();
+    }
+    let mut canvas = canvas_builder.build().map_err(|e| e.to_string())?;
+    canvas
+        .set_logical_size(view_width, view_height)
+        .map_err(|e| e.to_string())?;
+    Ok(canvas)
+}
@@ -1,136 +1,147 @@
-use std::collections::HashMap;
+use specs::{Join, LazyUpdate, LendJoin, World, WorldExt};
 
-use rand::Rng;
-use sdl2::rect::Point;
-use specs::{Builder, Join, World, WorldExt};
-
-const PLAYER_MOVE_SPEED: i32 = 5;
 const MAX_MISSILES: usize = 5;
-
-use crate::{components, GRID_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH, X_GRID_COUNT, Y_GRID_COUNT};
-
-pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>) {
-    let mut must_reload_world = false;
-    let mut create_asteroids_pressed = false;
-    let mut current_player_pos = components::Position {
-        pos: Point::new(0, 0),
-        rot: 0.0,
-    };
-    {
-        let players = ecs.read_storage::<crate::components::Player>();
-        let positions = ecs.read_storage::<crate::components::Position>();
-
-        for (position, _player) in (&positions, &players).join() {
-            current_player_pos.pos.x = position.pos.x;
-            current_player_pos.pos.y = position.pos.y;
-        }
-        if players.join().count() < 1 {
-            must_reload_world = true;
-        }
+// Asteroids added by the SpawnWave action.
+const STRESS_WAVE_SIZE: u32 = 20000;
+
+use crate::animation;
+use crate::archetypes::AsteroidArchetypes;
+use crate::boundary::PlayField;
+use crate::camera::Camera;
+use crate::components;
+use crate::input::{Action, Aim, InputState};
+use crate::movement::{ControlScheme, PlayerMovement};
+use crate::particles::{Particles, ROCKET_LAUNCH};
+use crate::prefab::{self, Prefab, PrefabRegistry};
+use crate::vector2D::Vector2D;
+use crate::waves::{AsteroidSpawner, WaveConfig};
+
+// Direction the held move actions point in, diagonals are as long as straight
+// moves so they are not faster.
+fn move_direction(input: &InputState) -> Vector2D {
+    let mut direction = Vector2D::new(0.0, 0.0);
+    if input.is_held(Action::MoveLeft) {
+        direction.x -= 1.0;
+    }
+    if input.is_held(Action::MoveRight) {
+        direction.x += 1.0;
+    }
+    if input.is_held(Action::MoveUp) {
+        direction.y -= 1.0;
+    }
+    if input.is_held(Action::MoveDown) {
+        direction.y += 1.0;
     }
+    direction.normalize()
+}
 
-    if must_reload_world {
-        ecs.delete_all();
-        load_world(ecs);
+// Heading the ship is being pushed along by the player, if it is at all.
+pub(crate) fn push_heading(
+    control_scheme: ControlScheme,
+    input: &InputState,
+    position: &components::Position,
+) -> Option<f64> {
+    match control_scheme {
+        // +90 cause player sprite is looking at the side
+        ControlScheme::Thrust => input.is_held(Action::MoveUp).then_some(position.rot + 90.0),
+        ControlScheme::Directional => {
+            let direction = move_direction(input);
+            (direction.magnitude() > 0.0).then(|| direction.heading())
+        }
     }
+}
 
-    let mut must_create_astroids = false;
-    let mut number_asteroids: u32 = 0;
+pub fn update_player(ecs: &World) {
+    let delta_time = ecs.read_resource::<components::DeltaTime>().0;
+    let input = ecs.read_resource::<InputState>();
+    let movement = ecs.read_resource::<PlayerMovement>();
+    let players = ecs.read_storage::<components::Player>();
+    let mut positions = ecs.write_storage::<components::Position>();
+    let mut velocities = ecs.write_storage::<components::Velocity>();
+    let mut animations = ecs.write_storage::<components::Animation>();
+
+    for (_, position, velocity, animation) in (
+        &players,
+        &mut positions,
+        &mut velocities,
+        (&mut animations).maybe(),
+    )
+        .join()
     {
-        let asteroids = ecs.read_storage::<components::Asteroid>();
-        if asteroids.count() < 1 && !create_asteroids_pressed {
-            must_create_astroids = true;
-            let mut gamedatas = ecs.write_storage::<components::GameData>();
-            for mut gamedata in (&mut gamedatas).join() {
-                gamedata.level += 1;
-                number_asteroids = (gamedata.level / 3) + 1;
-            }
+        if let Some(animation) = animation {
+            let pushed = push_heading(movement.control_scheme, &input, position).is_some();
+            animation.play(if pushed {
+                animation::THRUST
+            } else {
+                animation::IDLE
+            });
         }
-        if asteroids.count() as u32 == number_asteroids {
-            create_asteroids_pressed = false;
+        match movement.control_scheme {
+            ControlScheme::Directional => {
+                velocity.vel =
+                    movement
+                        .directional
+                        .apply(velocity.vel, move_direction(&input), delta_time);
+            }
+            ControlScheme::Thrust => {
+                let thrust = &movement.thrust;
+                if input.is_held(Action::MoveLeft) {
+                    position.rot -= movement.turn_speed * delta_time;
+                }
+                if input.is_held(Action::MoveRight) {
+                    position.rot += movement.turn_speed * delta_time;
+                }
+                // +90 cause player sprite is looking at the side
+                let facing = Vector2D::from_heading(position.rot + 90.0, 1.0);
+                velocity.vel = if input.is_held(Action::MoveUp) {
+                    thrust.apply(velocity.vel, facing, delta_time)
+                } else if input.is_held(Action::MoveDown) {
+                    thrust.brake(velocity.vel, delta_time)
+                } else {
+                    thrust.apply(velocity.vel, Vector2D::new(0.0, 0.0), delta_time)
+                };
+            }
         }
+        position.pos = position.pos.add(velocity.vel.multiply(delta_time));
     }
-    if crate::utils::is_key_pressed(&key_manager, "V") {
-        crate::utils::key_up(key_manager, "V".to_string());
-        number_asteroids += 20000;
-        create_asteroids_pressed = true;
+}
+
+pub fn store_previous_positions(ecs: &World) {
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<components::Position>();
+    let mut previous_positions = ecs.write_storage::<components::PreviousPosition>();
+
+    for (entity, position) in (&entities, &positions).join() {
+        previous_positions
+            .insert(entity, components::PreviousPosition { pos: position.pos })
+            .ok();
     }
-    if must_create_astroids || create_asteroids_pressed {
-        let mut asteroid_count: u32 = 0;
-        while asteroid_count < number_asteroids {
-            let mut rng = rand::thread_rng();
-            let size = rng.gen_range(1..6);
-            let next_x = rng.gen_range(50..SCREEN_WIDTH - 50);
-            let next_y = rng.gen_range(50..SCREEN_HEIGHT - 50);
-            let grid_x = X_GRID_COUNT * (next_x / GRID_SIZE);
-            let grid_y = Y_GRID_COUNT * (next_y / GRID_SIZE);
-            let next_rot = rng.gen_range(0.0..360.0);
-
-            let diff_x = ((current_player_pos.pos.x - next_x) as f64).abs();
-            let diff_y = ((current_player_pos.pos.y - next_y) as f64).abs();
-            if ((diff_x * diff_x) + (diff_y * diff_y)).sqrt() < 150.0 {
-                continue;
-            }
-            asteroid_count += 1;
-            let new_asteroid = components::Position {
-                pos: Point::new(next_x, next_y),
-                rot: next_rot,
-            };
-            let new_collider = components::Collider {
-                grid_x: grid_x,
-                grid_y: grid_y,
-            };
-
-            create_asteroid(ecs, new_asteroid, size, new_collider);
-        }
+}
+
+pub fn update(ecs: &mut World) {
+    let input = *ecs.read_resource::<InputState>();
+    if input.was_pressed(Action::SpawnWave) {
+        spawn_stress_wave(ecs);
     }
 
     let mut player_position = components::Position {
-        pos: Point::new(0, 0),
+        pos: Vector2D::new(0.0, 0.0),
         rot: 0.0,
     };
 
     let mut must_fire_rocket = false;
-    let mut player_grid_location = components::Collider {
-        grid_x: 0,
-        grid_y: 0,
-    };
     {
         let mut players = ecs.write_storage::<crate::components::Player>();
         let positions = ecs.read_storage::<crate::components::Position>();
-        let grid_location = ecs.read_storage::<crate::components::Collider>();
-        for (player, position, grid) in (&mut players, &positions, &grid_location).join() {
-            if crate::utils::is_key_pressed(&key_manager, "D") {
-                player.direction = components::Direction::Right;
-                player.speed = PLAYER_MOVE_SPEED;
-            } else if crate::utils::is_key_pressed(&key_manager, "A") {
-                player.direction = components::Direction::Left;
-                player.speed = PLAYER_MOVE_SPEED;
-            } else if crate::utils::is_key_pressed(&key_manager, "W") {
-                player.direction = components::Direction::Up;
-                player.speed = PLAYER_MOVE_SPEED;
-            } else if crate::utils::is_key_pressed(&key_manager, "S") {
-                player.direction = components::Direction::Down;
-                player.speed = PLAYER_MOVE_SPEED;
-            } else {
-                player.speed = 0;
-            }
-            if crate::utils::is_key_pressed(&key_manager, "C") {
-                crate::utils::key_up(key_manager, "C".to_string());
+        for (player, position) in (&mut players, &positions).join() {
+            if input.was_pressed(Action::ToggleGodMode) {
                 player.can_take_damage = !player.can_take_damage;
-                println!(
-                    "Player can take damage: {}",
-                    player.can_take_damage.to_string()
-                );
+                println!("Player can take damage: {}", player.can_take_damage);
             }
 
-            if crate::utils::is_key_pressed(&key_manager, "Space") {
+            if input.was_pressed(Action::Fire) {
                 must_fire_rocket = true;
-                crate::utils::key_up(key_manager, "Space".to_string());
-                player_position.pos.x = position.pos.x;
-                player_position.pos.y = position.pos.y;
-                player_grid_location.grid_x = grid.grid_x;
-                player_grid_location.grid_y = grid.grid_y;
+                player_position.pos = position.pos;
                 player_position.rot = position.rot + 90.0; // +90 cause player sprite is looking at the side
             } else {
                 must_fire_rocket = false;
@@ -139,17 +150,57 @@ pub fn update(ecs: &mut World, key_manager: &mut HashMap<String, bool>) {
     }
 
     if must_fire_rocket {
-        fire_rocket(ecs, player_position, player_grid_location);
+        fire_rocket(ecs, player_position);
     }
 }
-pub fn update_player_rotation(ecs: &mut World, mouse_position: Point) {
+// Throws a huge number of asteroids in on top of the current wave, to see how
+// the game holds up.
+fn spawn_stress_wave(ecs: &World) {
+    let avoid = {
+        let players = ecs.read_storage::<components::Player>();
+        let positions = ecs.read_storage::<components::Position>();
+        (&players, &positions)
+            .join()
+            .map(|(_, position)| position.pos)
+            .next()
+            .unwrap_or(ecs.read_resource::<PlayField>().center())
+    };
+    let archetypes = ecs.read_resource::<AsteroidArchetypes>();
+    let spawner = AsteroidSpawner {
+        archetypes: &archetypes,
+        prefabs: &ecs.read_resource::<PrefabRegistry>(),
+        lazy: &ecs.read_resource::<LazyUpdate>(),
+        entities: &ecs.entities(),
+        field: &ecs.read_resource::<PlayField>(),
+    };
+    spawner.spawn(
+        STRESS_WAVE_SIZE,
+        |rng| Some(archetypes.random_tier(rng)),
+        1.0,
+        avoid,
+        ecs.read_resource::<WaveConfig>().safe_radius,
+        &mut *ecs.write_resource::<components::GameRng>(),
+    );
+}
+
+pub fn update_player_rotation(ecs: &mut World, aim: Aim) {
+    let control_scheme = ecs.read_resource::<PlayerMovement>().control_scheme;
+    // The mouse points at the screen, the camera knows what is under it
+    let aim = match aim {
+        Aim::At(target) => Aim::At(ecs.read_resource::<Camera>().screen_to_world(target)),
+        towards => towards,
+    };
     let mut renderables = ecs.write_storage::<crate::components::Renderable>();
     let players = ecs.read_component::<crate::components::Player>();
     let mut positions = ecs.write_component::<crate::components::Position>();
     for (renderable, _, position) in (&mut renderables, &players, &mut positions).join() {
-        let delta_x = (mouse_position.x - position.pos.x) as f64;
-        let delta_y = (mouse_position.y - position.pos.y) as f64;
-        let angle = delta_y.atan2(delta_x);
+        // The thrust scheme turns the ship in update_player, aiming only fires
+        if control_scheme == ControlScheme::Thrust {
+            renderable.render_rotation = position.rot;
+            continue;
+        }
+        let delta = aim.direction_from(position.pos);
+        let angle = delta.y.atan2(delta.x);
 
         let angle_degrees = angle.to_degrees();
         renderable.render_rotation = angle_degrees;
@@ -157,96 +208,38 @@ pub fn update_player_rotation(ecs: &mut World, mouse_position: Point) {
     }
 }
 
+// The asteroids come with the first wave, see WaveDirectorSystem.
 pub fn load_world(ecs: &mut World) {
-    ecs.create_entity()
-        .with(components::Position {
-            pos: Point::new(50, 50),
-            rot: 270.0,
-        })
-        .with(components::Renderable {
-            texture_name: String::from("marco"),
-            input_width: 32,
-            input_height: 42,
-            output_width: 32,
-            output_height: 42,
-            frame: 1,
-            total_frames: 9,
-            render_rotation: 0.0,
-        })
-        .with(components::Player {
-            speed: 0,
-            direction: components::Direction::Right,
-            can_take_damage: true,
-        })
-        .with(components::Collider {
-            grid_x: 0,
-            grid_y: 0,
-        })
-        .build();
-    create_asteroid(
-        ecs,
-        components::Position {
-            pos: Point::new(200, 400),
-            rot: 45.0,
-        },
-        2,
-        components::Collider {
-            grid_x: X_GRID_COUNT * 2,
-            grid_y: Y_GRID_COUNT * 4,
-        },
-    );
-    ecs.create_entity()
-        .with(components::GameData { score: 0, level: 1 })
-        .build();
+    prefab::spawn(ecs, prefab::PLAYER, Prefab::default());
+    prefab::spawn(ecs, prefab::GAME_DATA, Prefab::default());
 }
 
-fn fire_rocket(ecs: &mut World, position: components::Position, collider: components::Collider) {
-    {
+fn fire_rocket(ecs: &mut World, position: components::Position) {
+    let rocket_speed = {
         let rockets = ecs.read_storage::<crate::components::Rocket>();
         if rockets.count() > MAX_MISSILES - 1 {
             return;
         }
-    }
-    ecs.create_entity()
-        .with(position)
-        .with(components::Renderable {
-            texture_name: String::from("rocket"),
-            input_width: 17,
-            input_height: 61,
-            output_width: 40,
-            output_height: 61,
-            frame: 0,
-            total_frames: 1,
-            render_rotation: 0.0,
-        })
-        .with(collider)
-        .with(components::Rocket { speed: 10.0 })
-        .build();
-}
-
-fn create_asteroid(
-    ecs: &mut World,
-    position: components::Position,
-    asteroid_size_mult: u32,
-    collider: components::Collider,
-) {
-    ecs.create_entity()
-        .with(position)
-        .with(components::Renderable {
-            texture_name: String::from("enemy"),
-            input_width: 33,
-            input_height: 45,
-            output_width: 33 * asteroid_size_mult,
-            output_height: 45 * asteroid_size_mult,
-            frame: 1,
-            total_frames: 12,
-            render_rotation: 0.0,
-        })
-        .with(crate::components::Asteroid {
-            speed: 4.0,
-            rotation_speed: 0.5,
-            size_multiplier: asteroid_size_mult,
-        })
-        .with(collider)
-        .build();
+        let registry = ecs.read_resource::<PrefabRegistry>();
+        match registry
+            .get(prefab::ROCKET)
+            .and_then(|rocket| rocket.rocket.as_ref())
+        {
+            Some(rocket) => rocket.speed,
+            None => return,
+        }
+    };
+    ecs.write_resource::<Particles>()
+        .burst(ROCKET_LAUNCH, position.pos, position.rot, 1.0);
+    prefab::spawn(
+        ecs,
+        prefab::ROCKET,
+        Prefab {
+            velocity: Some(components::Velocity {
+                vel: Vector2D::from_heading(position.rot, rocket_speed),
+            }),
+            position: Some(position),
+            ..Prefab::default()
+        },
+    );
 }
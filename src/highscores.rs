@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::storage;
+
+// Bump whenever the file layout changes, files with another version are not
+// read.
+pub const HIGH_SCORES_VERSION: u32 = 1;
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_INITIALS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: u32,
+    pub level: u32,
+    // Day the score was set, as YYYY-MM-DD.
+    pub date: String,
+    // Control scheme the score was set with.
+    pub mode: String,
+}
+
+// The best scores, highest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScoreTable {
+    version: u32,
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl Default for HighScoreTable {
+    fn default() -> Self {
+        HighScoreTable {
+            version: HIGH_SCORES_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl HighScoreTable {
+    // A missing file is an empty table. A file that cannot be read is moved
+    // out of the way to `<name>.corrupt` so it is not overwritten, and the game
+    // carries on with an empty table instead of refusing to start.
+    pub fn load(path: &Path) -> Self {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                println!("Cannot read high scores {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        match Self::from_ron(&text) {
+            Ok(table) => table,
+            Err(e) => {
+                let backup = path.with_extension("ron.corrupt");
+                println!(
+                    "High scores {} are unreadable ({}), moved to {}",
+                    path.display(),
+                    e,
+                    backup.display()
+                );
+                std::fs::rename(path, &backup).ok();
+                Self::default()
+            }
+        }
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let mut table: HighScoreTable = ron::from_str(text).map_err(|e| e.to_string())?;
+        if table.version != HIGH_SCORES_VERSION {
+            return Err(format!("unsupported version {}", table.version));
+        }
+        table
+            .entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        table.entries.truncate(MAX_HIGH_SCORES);
+        Ok(table)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        storage::write_atomically(path, &text)
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    // Puts the entry in its place and returns its index, or None when it did
+    // not make the table. Ties go below the scores that were there first.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|existing| existing.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(index)
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    storage::data_file("highscores.ron")
+}
+
+// Today's date in UTC as YYYY-MM-DD.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86400)
+        .unwrap_or(0) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Days since 1970-01-01 to a (year, month, day) civil date, counting in 400
+// year eras that start on March 1st so leap days fall at the end of a year.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month as u32, day as u32)
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use asteroidgame::assets::{self, AssetKind, AssetManifest, AssetWatcher};
+use asteroidgame::simulation::Definitions;
+
+use crate::scenes::Context;
+
+// How often the asset files are checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+pub fn watcher(manifest: &AssetManifest) -> AssetWatcher {
+    AssetWatcher::new(manifest, CHECK_INTERVAL)
+}
+
+// Puts assets saved since the last check into the running game. A file that
+// cannot be loaded is reported and the game carries on with what it had.
+pub fn reload_changed(watcher: &mut AssetWatcher, manifest: &AssetManifest, ctx: &mut Context) {
+    let mut definitions_changed = false;
+    for (kind, name) in watcher.changed() {
+        match kind {
+            AssetKind::Texture => {
+                let path = &manifest.textures[&name];
+                match ctx.texture_manager.load_texture(&name, path) {
+                    Ok(()) => {
+                        ctx.texture_manager.forget_missing_sprites();
+                        println!("Reloaded texture {}", path);
+                    }
+                    Err(e) => println!("Cannot reload texture {}: {}", path, e),
+                }
+            }
+            AssetKind::Data
+                if [assets::ASTEROIDS, assets::ATLASES, assets::PARTICLES]
+                    .contains(&name.as_str())
+                    || ctx
+                        .simulation
+                        .config()
+                        .atlases
+                        .sheets()
+                        .any(|sheet| sheet == name) =>
+            {
+                definitions_changed = true;
+            }
+            _ => println!("{} {:?} changed, restart to use it", kind.name(), name),
+        }
+    }
+
+    if definitions_changed {
+        let reloaded =
+            Definitions::load(manifest, &ctx.simulation.config().waves).and_then(|definitions| {
+                ctx.texture_manager.check_atlases(&definitions.atlases)?;
+                Ok(definitions)
+            });
+        match reloaded {
+            Ok(definitions) => {
+                ctx.simulation.reload_definitions(&definitions);
+                ctx.texture_manager.forget_missing_sprites();
+                println!("Reloaded asteroid, atlas and particle definitions");
+            }
+            Err(e) => println!("Cannot reload definitions: {}", e),
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use crate::simulation::InputSnapshot;
+use crate::vector2D::Vector2D;
+
+// Everything the player can ask the game to do, independent of the device the
+// request came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    ToggleGodMode,
+    SpawnWave,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::ToggleGodMode,
+        Action::SpawnWave,
+        Action::Pause,
+    ];
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+// A set of actions packed into a bitmask, small enough to copy around every tick.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionSet(u32);
+
+impl ActionSet {
+    pub fn contains(self, action: Action) -> bool {
+        self.0 & action.bit() != 0
+    }
+
+    pub fn insert(&mut self, action: Action) {
+        self.0 |= action.bit();
+    }
+
+    pub fn remove(&mut self, action: Action) {
+        self.0 &= !action.bit();
+    }
+
+    pub fn union(self, other: ActionSet) -> ActionSet {
+        ActionSet(self.0 | other.0)
+    }
+
+    // Actions in `self` that are not in `other`.
+    pub fn difference(self, other: ActionSet) -> ActionSet {
+        ActionSet(self.0 & !other.0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+// Input as the simulation sees it during a tick. Held is what is down right now,
+// pressed and released only hold an action on the tick its state changed.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct InputState {
+    held: ActionSet,
+    pressed: ActionSet,
+    released: ActionSet,
+    pub aim: Aim,
+}
+
+impl InputState {
+    pub fn update(&mut self, snapshot: &InputSnapshot) {
+        self.pressed = snapshot.actions.difference(self.held);
+        self.released = self.held.difference(snapshot.actions);
+        self.held = snapshot.actions;
+        self.aim = snapshot.aim;
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held.contains(action)
+    }
+
+    pub fn was_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(action)
+    }
+
+    pub fn was_released(&self, action: Action) -> bool {
+        self.released.contains(action)
+    }
+}
+
+// Where the player wants to point. A mouse gives a position on screen, a stick
+// only gives a direction relative to the player.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Aim {
+    At(Vector2D),
+    Towards(Vector2D),
+}
+
+impl Default for Aim {
+    fn default() -> Self {
+        Aim::At(Vector2D::default())
+    }
+}
+
+impl Aim {
+    // Direction to aim in for something standing at `from`.
+    pub fn direction_from(self, from: Vector2D) -> Vector2D {
+        match self {
+            Aim::At(target) => target.substract(from),
+            Aim::Towards(direction) => direction,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+// Device independent input, the front-end translates whatever it reads into
+// these after looking the key or button up in its bindings. Controller input
+// carries the id of the controller it came from.
+#[derive(Debug, Clone, Copy)]
+pub enum InputEvent {
+    Down(Action),
+    Up(Action),
+    AimAt(Vector2D),
+    PadDown(u32, Action),
+    PadUp(u32, Action),
+    // Sticks go from -1.0 to 1.0 with positive y pointing down, triggers go
+    // from 0.0 to 1.0.
+    PadAxis(u32, PadAxis, f64),
+    // The controller went away, anything it was holding is let go.
+    PadDisconnected(u32),
+}
+
+impl InputEvent {
+    // The action a key or button press starts.
+    pub fn pressed(self) -> Option<Action> {
+        match self {
+            InputEvent::Down(action) | InputEvent::PadDown(_, action) => Some(action),
+            _ => None,
+        }
+    }
+}
+
+// How analog controller input turns into actions.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct GamepadSettings {
+    // Stick deflection below this is treated as the stick resting.
+    pub dead_zone: f64,
+    // How far a trigger has to be pulled to count as pressed.
+    pub trigger_threshold: f64,
+    pub left_trigger: Option<Action>,
+    pub right_trigger: Option<Action>,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        GamepadSettings {
+            dead_zone: 0.25,
+            trigger_threshold: 0.5,
+            left_trigger: None,
+            right_trigger: Some(Action::Fire),
+        }
+    }
+}
+
+// How many keys or buttons currently hold each action down, so releasing one
+// of two keys bound to the same action keeps it held.
+#[derive(Debug, Default, Clone, Copy)]
+struct HeldCounts([u8; Action::ALL.len()]);
+
+impl HeldCounts {
+    fn press(&mut self, action: Action) {
+        let count = &mut self.0[action as usize];
+        *count = count.saturating_add(1);
+    }
+
+    fn release(&mut self, action: Action) {
+        let count = &mut self.0[action as usize];
+        *count = count.saturating_sub(1);
+    }
+
+    fn add_to(&self, held: &mut ActionSet) {
+        for action in Action::ALL {
+            if self.0[action as usize] > 0 {
+                held.insert(action);
+            }
+        }
+    }
+}
+
+// What one controller is holding.
+#[derive(Debug, Default)]
+struct Pad {
+    buttons: HeldCounts,
+    axes: [f64; 6],
+}
+
+impl Pad {
+    fn axis(&self, axis: PadAxis) -> f64 {
+        self.axes[axis as usize]
+    }
+}
+
+// Turns a stream of InputEvents into one InputSnapshot per tick. A press that
+// is released again before the next tick still shows up in that tick, so quick
+// taps are never lost when frames run faster than the simulation.
+#[derive(Debug, Default)]
+pub struct InputCollector {
+    gamepad: GamepadSettings,
+    keys: HeldCounts,
+    // Every controller that sent input, by id, so unplugging one only lets go
+    // of what that one was holding.
+    pads: HashMap<u32, Pad>,
+    // Actions held by the sticks and triggers of all pads together.
+    pad_held: ActionSet,
+    tapped: ActionSet,
+    aim: Aim,
+}
+
+impl InputCollector {
+    pub fn new(gamepad: GamepadSettings) -> Self {
+        InputCollector {
+            gamepad,
+            ..InputCollector::default()
+        }
+    }
+
+    pub fn handle(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Down(action) => {
+                self.keys.press(action);
+                self.tapped.insert(action);
+            }
+            InputEvent::Up(action) => self.keys.release(action),
+            InputEvent::AimAt(aim) => self.aim = Aim::At(aim),
+            InputEvent::PadDown(pad, action) => {
+                self.pads.entry(pad).or_default().buttons.press(action);
+                self.tapped.insert(action);
+            }
+            InputEvent::PadUp(pad, action) => {
+                if let Some(pad) = self.pads.get_mut(&pad) {
+                    pad.buttons.release(action);
+                }
+            }
+            InputEvent::PadAxis(pad, axis, value) => {
+                let pad = self.pads.entry(pad).or_default();
+                pad.axes[axis as usize] = value;
+                // A resting right stick keeps the last direction, so letting
+                // go of it does not snap the ship back to where the mouse is.
+                let right = Vector2D::new(pad.axis(PadAxis::RightX), pad.axis(PadAxis::RightY));
+                if right.magnitude() > self.gamepad.dead_zone {
+                    self.aim = Aim::Towards(right);
+                }
+                self.update_pad();
+            }
+            InputEvent::PadDisconnected(pad) => {
+                self.pads.remove(&pad);
+                self.update_pad();
+            }
+        }
+    }
+
+    fn update_pad(&mut self) {
+        let mut held = ActionSet::default();
+        for pad in self.pads.values() {
+            held = held.union(self.stick_actions(pad));
+        }
+        self.tapped = self.tapped.union(held.difference(self.pad_held));
+        self.pad_held = held;
+    }
+
+    // Actions one pad's sticks and triggers are holding.
+    fn stick_actions(&self, pad: &Pad) -> ActionSet {
+        let dead_zone = self.gamepad.dead_zone;
+        let mut held = ActionSet::default();
+
+        let left_x = pad.axis(PadAxis::LeftX);
+        let left_y = pad.axis(PadAxis::LeftY);
+        if left_x > dead_zone {
+            held.insert(Action::MoveRight);
+        } else if left_x < -dead_zone {
+            held.insert(Action::MoveLeft);
+        }
+        if left_y > dead_zone {
+            held.insert(Action::MoveDown);
+        } else if left_y < -dead_zone {
+            held.insert(Action::MoveUp);
+        }
+
+        let triggers = [
+            (PadAxis::LeftTrigger, self.gamepad.left_trigger),
+            (PadAxis::RightTrigger, self.gamepad.right_trigger),
+        ];
+        for (axis, action) in triggers {
+            if let Some(action) = action {
+                if pad.axis(axis) > self.gamepad.trigger_threshold {
+                    held.insert(action);
+                }
+            }
+        }
+        held
+    }
+
+    pub fn held(&self) -> ActionSet {
+        let mut held = self.pad_held;
+        self.keys.add_to(&mut held);
+        for pad in self.pads.values() {
+            pad.buttons.add_to(&mut held);
+        }
+        held
+    }
+
+    pub fn snapshot(&mut self) -> InputSnapshot {
+        let actions = self.held().union(self.tapped);
+        self.tapped = ActionSet::default();
+        InputSnapshot {
+            actions,
+            aim: self.aim,
+        }
+    }
+
+    // Forgets every held action, for when key releases can no longer be seen,
+    // like while another scene has the input.
+    pub fn release_all(&mut self) {
+        self.keys = HeldCounts::default();
+        self.pads.clear();
+        self.pad_held = ActionSet::default();
+        self.tapped = ActionSet::default();
+    }
+}
+
+// Which key or controller button triggers which action. Both are stored by
+// their SDL name ("W", "Space", "Left Shift", "a", "start") so the simulation
+// does not need to depend on SDL.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Bindings {
+    pub keys: HashMap<String, Action>,
+    #[serde(default)]
+    pub buttons: HashMap<String, Action>,
+    #[serde(default)]
+    pub gamepad: GamepadSettings,
+}
+
+impl Bindings {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read key bindings {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        ron::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn action_for_key(&self, key_name: &str) -> Option<Action> {
+        self.keys.get(key_name).copied()
+    }
+
+    pub fn action_for_button(&self, button_name: &str) -> Option<Action> {
+        self.buttons.get(button_name).copied()
+    }
+
+    // One of the keys bound to `action`, for telling the player what to press.
+    pub fn key_for(&self, action: Action) -> Option<&str> {
+        self.keys
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(key_name, _)| key_name.as_str())
+            .min()
+    }
+}
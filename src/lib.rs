@@ -0,0 +1,29 @@
+pub mod animation;
+pub mod archetypes;
+pub mod assets;
+pub mod asteroid;
+pub mod atlas;
+pub mod boundary;
+pub mod broadphase;
+pub mod camera;
+pub mod collision;
+pub mod components;
+pub mod game;
+pub mod highscores;
+pub mod input;
+pub mod lives;
+pub mod movement;
+pub mod particles;
+pub mod prefab;
+pub mod replay;
+pub mod responses;
+pub mod rocket;
+pub mod save;
+pub mod sheet;
+pub mod simulation;
+pub mod storage;
+#[allow(non_snake_case)]
+pub mod vector2D;
+pub mod waves;
+
+pub const GRID_SIZE: i32 = 100;
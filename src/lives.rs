@@ -0,0 +1,202 @@
+use serde::Deserialize;
+use specs::prelude::*;
+use specs::{Entities, Join};
+
+use crate::boundary::PlayField;
+use crate::components;
+use crate::prefab::{self, Prefab, PrefabRegistry};
+use crate::vector2D::Vector2D;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LivesConfig {
+    pub starting_lives: u32,
+    // Seconds between losing a ship and the next one appearing.
+    pub respawn_delay: f64,
+    // Seconds a new ship cannot be hurt.
+    pub invulnerability: f64,
+    // A ship only appears where no asteroid is closer than this.
+    pub safe_radius: f64,
+    // Scores at which an extra life is awarded, in increasing order.
+    pub extra_life_scores: Vec<u32>,
+    // After the last score in the list, another life every this many points.
+    pub extra_life_every: Option<u32>,
+}
+
+impl LivesConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read lives config {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let config: LivesConfig = ron::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.starting_lives == 0 {
+            return Err("starting_lives must be at least 1".to_string());
+        }
+        if !self.safe_radius.is_finite() || self.safe_radius < 0.0 {
+            return Err("safe_radius cannot be negative".to_string());
+        }
+        if self
+            .extra_life_scores
+            .windows(2)
+            .any(|pair| pair[0] >= pair[1])
+        {
+            return Err("extra_life_scores must be in increasing order".to_string());
+        }
+        if self.extra_life_every == Some(0) {
+            return Err("extra_life_every cannot be 0".to_string());
+        }
+        Ok(())
+    }
+
+    // Score needed for the extra life after `awarded` have been handed out,
+    // None once there are no more or the score would not fit in a u32.
+    pub fn extra_life_score(&self, awarded: u32) -> Option<u32> {
+        if let Some(score) = self.extra_life_scores.get(awarded as usize) {
+            return Some(*score);
+        }
+        let every = self.extra_life_every?;
+        let last = self.extra_life_scores.last().copied().unwrap_or(0);
+        let past_list = (awarded - self.extra_life_scores.len() as u32).checked_add(1)?;
+        last.checked_add(every.checked_mul(past_list)?)
+    }
+}
+
+// Places a new ship may appear, the center first and then spots spread over the
+// field, so it comes back somewhere predictable whenever that is clear.
+fn respawn_candidates(field: &PlayField) -> impl Iterator<Item = Vector2D> {
+    let size = field.size();
+    let spread = (1..4).flat_map(move |row| {
+        (1..4).map(move |column| {
+            Vector2D::new(size.x * column as f64 / 4.0, size.y * row as f64 / 4.0)
+        })
+    });
+    std::iter::once(field.center()).chain(spread)
+}
+
+// Counts down the respawn delay and brings the next ship in at the first spot
+// clear of asteroids. When none is clear it keeps trying every tick.
+pub struct Respawner;
+
+impl<'a> System<'a> for Respawner {
+    type SystemData = (
+        WriteStorage<'a, components::GameData>,
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::Collider>,
+        ReadExpect<'a, LivesConfig>,
+        ReadExpect<'a, PrefabRegistry>,
+        ReadExpect<'a, PlayField>,
+        Read<'a, components::DeltaTime>,
+        Read<'a, LazyUpdate>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut gamedatas,
+            positions,
+            asteroids,
+            colliders,
+            config,
+            prefabs,
+            field,
+            delta_time,
+            lazy,
+            entities,
+        ) = data;
+
+        for gamedata in (&mut gamedatas).join() {
+            let remaining = match gamedata.respawn_timer {
+                Some(remaining) => remaining - delta_time.0,
+                None => continue,
+            };
+            gamedata.respawn_timer = Some(remaining.max(0.0));
+            if remaining > 0.0 {
+                continue;
+            }
+
+            let is_clear = |spot: Vector2D| {
+                (&positions, &asteroids, &colliders)
+                    .join()
+                    .all(|(position, _, collider)| {
+                        field.delta(spot, position.pos, true).magnitude()
+                            > config.safe_radius + collider.radius
+                    })
+            };
+            if let Some(spot) = respawn_candidates(&field).find(|spot| is_clear(*spot)) {
+                gamedata.respawn_timer = None;
+                prefabs.spawn_lazy(
+                    prefab::PLAYER,
+                    &lazy,
+                    &entities,
+                    Prefab {
+                        position: Some(components::Position {
+                            pos: spot,
+                            rot: 270.0,
+                        }),
+                        invulnerable: Some(components::Invulnerable {
+                            remaining: config.invulnerability,
+                        }),
+                        ..Prefab::default()
+                    },
+                );
+            }
+        }
+    }
+}
+
+pub struct InvulnerabilityTimer;
+
+impl<'a> System<'a> for InvulnerabilityTimer {
+    type SystemData = (
+        WriteStorage<'a, components::Invulnerable>,
+        Read<'a, components::DeltaTime>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut invulnerables, delta_time, entities) = data;
+        let mut expired = Vec::new();
+
+        for (invulnerable, entity) in (&mut invulnerables, &entities).join() {
+            invulnerable.remaining -= delta_time.0;
+            if invulnerable.remaining <= 0.0 {
+                expired.push(entity);
+            }
+        }
+        for entity in expired {
+            invulnerables.remove(entity);
+        }
+    }
+}
+
+// Hands out a life for every threshold the score has passed.
+pub struct ExtraLives;
+
+impl<'a> System<'a> for ExtraLives {
+    type SystemData = (
+        WriteStorage<'a, components::GameData>,
+        ReadExpect<'a, LivesConfig>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut gamedatas, config) = data;
+
+        for gamedata in (&mut gamedatas).join() {
+            while let Some(score) = config.extra_life_score(gamedata.extra_lives_awarded) {
+                if gamedata.score < score {
+                    break;
+                }
+                gamedata.extra_lives_awarded += 1;
+                gamedata.lives += 1;
+            }
+        }
+    }
+}
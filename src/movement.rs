@@ -0,0 +1,99 @@
+use serde::Deserialize;
+
+use crate::vector2D::Vector2D;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ControlScheme {
+    // Move keys push the ship in that direction on screen, the ship faces the
+    // aim.
+    Directional,
+    // Left and right turn the ship, up thrusts along the way it is facing and
+    // down brakes, like the arcade original.
+    Thrust,
+}
+
+impl ControlScheme {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "directional" => Some(ControlScheme::Directional),
+            "thrust" => Some(ControlScheme::Thrust),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlScheme::Directional => "directional",
+            ControlScheme::Thrust => "thrust",
+        }
+    }
+}
+
+// How the ship speeds up and slows down. Speeds are in pixels per second.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct MovementTuning {
+    // Speed gained per second while pushing.
+    pub acceleration: f64,
+    pub max_speed: f64,
+    // Fraction of the speed lost per second.
+    pub drag: f64,
+}
+
+impl MovementTuning {
+    // Velocity after `delta_time` seconds of pushing along `direction`, which
+    // is either zero or of length one.
+    pub fn apply(&self, velocity: Vector2D, direction: Vector2D, delta_time: f64) -> Vector2D {
+        let velocity = velocity
+            .add(direction.multiply(self.acceleration * delta_time))
+            .multiply((1.0 - self.drag * delta_time).max(0.0));
+        let speed = velocity.magnitude();
+        if speed > self.max_speed {
+            velocity.multiply(self.max_speed / speed)
+        } else {
+            velocity
+        }
+    }
+
+    // Velocity after `delta_time` seconds of braking, never reversing it.
+    pub fn brake(&self, velocity: Vector2D, delta_time: f64) -> Vector2D {
+        let speed = velocity.magnitude();
+        let slowed = (speed - self.acceleration * delta_time).max(0.0);
+        if speed > 0.0 {
+            velocity.multiply(slowed / speed)
+        } else {
+            velocity
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlayerMovement {
+    pub control_scheme: ControlScheme,
+    pub directional: MovementTuning,
+    pub thrust: MovementTuning,
+    // Degrees per second the ship turns with the thrust scheme.
+    pub turn_speed: f64,
+}
+
+impl PlayerMovement {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read player movement {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let movement: PlayerMovement = ron::from_str(text).map_err(|e| e.to_string())?;
+        movement.validate()?;
+        Ok(movement)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, tuning) in [("directional", self.directional), ("thrust", self.thrust)] {
+            if tuning.acceleration < 0.0 || tuning.max_speed < 0.0 || tuning.drag < 0.0 {
+                return Err(format!("Movement values for {} cannot be negative", name));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,364 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use specs::prelude::*;
+use specs::shrev::{EventChannel, ReaderId};
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::archetypes::AsteroidArchetypes;
+use crate::assets::AssetManifest;
+use crate::collision::CollisionEvent;
+use crate::components;
+use crate::game;
+use crate::input::InputState;
+use crate::movement::PlayerMovement;
+use crate::responses::can_be_hurt;
+use crate::vector2D::Vector2D;
+
+// Emitters the game triggers, particles.ron has to define all of them.
+pub const ASTEROID_EXPLOSION: &str = "asteroid_explosion";
+pub const SHIP_EXPLOSION: &str = "ship_explosion";
+pub const ROCKET_LAUNCH: &str = "rocket_launch";
+pub const ROCKET_TRAIL: &str = "rocket_trail";
+pub const THRUSTER: &str = "thruster";
+const EMITTER_NAMES: [&str; 5] = [
+    ASTEROID_EXPLOSION,
+    SHIP_EXPLOSION,
+    ROCKET_LAUNCH,
+    ROCKET_TRAIL,
+    THRUSTER,
+];
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Range {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Range {
+    fn pick<R: Rng>(&self, rng: &mut R) -> f64 {
+        if self.min < self.max {
+            rng.gen_range(self.min..self.max)
+        } else {
+            self.min
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmitterConfig {
+    pub burst: u32,
+    pub rate: f64,
+    pub lifetime: Range,
+    pub speed: Range,
+    pub spread: f64,
+    pub drag: f64,
+    pub start_size: f64,
+    pub end_size: f64,
+    pub colors: Vec<(u8, u8, u8, u8)>,
+    pub texture: Option<String>,
+}
+
+impl EmitterConfig {
+    // Color `t` of the way through a particle's life, blended between the two
+    // nearest keys.
+    fn color_at(&self, t: f64) -> (u8, u8, u8, u8) {
+        let last = self.colors.len() - 1;
+        let scaled = t.clamp(0.0, 1.0) * last as f64;
+        let index = (scaled as usize).min(last);
+        let (from, to) = (self.colors[index], self.colors[(index + 1).min(last)]);
+        let blend = scaled - index as f64;
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * blend).round() as u8;
+        (
+            mix(from.0, to.0),
+            mix(from.1, to.1),
+            mix(from.2, to.2),
+            mix(from.3, to.3),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParticleConfig {
+    pub max_particles: usize,
+    pub emitters: BTreeMap<String, EmitterConfig>,
+}
+
+impl ParticleConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read particle config {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let config: ParticleConfig = ron::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for name in EMITTER_NAMES {
+            if !self.emitters.contains_key(name) {
+                return Err(format!("Emitter {:?} is missing", name));
+            }
+        }
+        for (name, emitter) in self.emitters.iter() {
+            if emitter.colors.is_empty() {
+                return Err(format!("Emitter {:?} has no colors", name));
+            }
+            if emitter.lifetime.min <= 0.0 {
+                return Err(format!("Emitter {:?} needs a lifetime above 0", name));
+            }
+            if emitter.lifetime.min > emitter.lifetime.max || emitter.speed.min > emitter.speed.max
+            {
+                return Err(format!("Emitter {:?} has a min above its max", name));
+            }
+            if emitter.rate < 0.0 || emitter.drag < 0.0 {
+                return Err(format!(
+                    "Emitter {:?} cannot have a negative rate or drag",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Emitters can only tint textures the asset manifest lists.
+    pub fn check_textures(&self, manifest: &AssetManifest) -> Result<(), String> {
+        for (name, emitter) in self.emitters.iter() {
+            if let Some(texture) = &emitter.texture {
+                if !manifest.textures.contains_key(texture) {
+                    return Err(format!(
+                        "Emitter {:?} uses texture {:?}, which the asset manifest does not list",
+                        name, texture
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vector2D,
+    velocity: Vector2D,
+    age: f64,
+    lifetime: f64,
+    emitter: usize,
+}
+
+// What the renderer needs to draw one particle.
+pub struct ParticleSprite<'a> {
+    pub position: Vector2D,
+    pub velocity: Vector2D,
+    pub size: f64,
+    pub color: (u8, u8, u8, u8),
+    pub texture: Option<&'a str>,
+}
+
+// Every live particle, kept in one flat list rather than as entities so tens of
+// thousands of them stay cheap. Nothing in the game reacts to particles, so
+// they have their own random numbers and are left out of saves and checksums.
+pub struct Particles {
+    emitters: Vec<EmitterConfig>,
+    names: HashMap<String, usize>,
+    particles: Vec<Particle>,
+    max_particles: usize,
+    rng: ChaCha8Rng,
+}
+
+impl Particles {
+    pub fn new(config: &ParticleConfig, seed: u64) -> Self {
+        Particles {
+            emitters: config.emitters.values().cloned().collect(),
+            names: config
+                .emitters
+                .keys()
+                .enumerate()
+                .map(|(index, name)| (name.clone(), index))
+                .collect(),
+            particles: Vec::with_capacity(config.max_particles),
+            max_particles: config.max_particles,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    // Sends out an emitter's burst from `position`, in a cone around `heading`.
+    // `scale` multiplies the number of particles, for bigger explosions.
+    pub fn burst(&mut self, name: &str, position: Vector2D, heading: f64, scale: f64) {
+        if let Some(&emitter) = self.names.get(name) {
+            let count = (self.emitters[emitter].burst as f64 * scale).round() as u32;
+            self.spawn(emitter, count, position, heading);
+        }
+    }
+
+    // An emitter's steady flow over `delta_time` seconds, for things that leave
+    // a trail. Fractions of a particle are rounded at random so low rates
+    // still come out right on average.
+    pub fn stream(&mut self, name: &str, position: Vector2D, heading: f64, delta_time: f64) {
+        if let Some(&emitter) = self.names.get(name) {
+            let expected = self.emitters[emitter].rate * delta_time;
+            let count = (expected + self.rng.gen::<f64>()) as u32;
+            self.spawn(emitter, count, position, heading);
+        }
+    }
+
+    fn spawn(&mut self, emitter: usize, count: u32, position: Vector2D, heading: f64) {
+        let config = &self.emitters[emitter];
+        let room = self.max_particles.saturating_sub(self.particles.len());
+        for _ in 0..(count as usize).min(room) {
+            let direction = heading + self.rng.gen_range(-0.5..=0.5) * config.spread;
+            self.particles.push(Particle {
+                position,
+                velocity: Vector2D::from_heading(direction, config.speed.pick(&mut self.rng)),
+                age: 0.0,
+                lifetime: config.lifetime.pick(&mut self.rng),
+                emitter,
+            });
+        }
+    }
+
+    // Moves every particle and drops the ones that have lived out their time.
+    pub fn update(&mut self, delta_time: f64) {
+        let emitters = &self.emitters;
+        self.particles.retain_mut(|particle| {
+            particle.age += delta_time;
+            let drag = (1.0 - emitters[particle.emitter].drag * delta_time).max(0.0);
+            particle.velocity = particle.velocity.multiply(drag);
+            particle.position = particle
+                .position
+                .add(particle.velocity.multiply(delta_time));
+            particle.age < particle.lifetime
+        });
+    }
+
+    pub fn sprites(&self) -> impl Iterator<Item = ParticleSprite<'_>> + '_ {
+        self.particles.iter().map(move |particle| {
+            let emitter = &self.emitters[particle.emitter];
+            let t = particle.age / particle.lifetime;
+            ParticleSprite {
+                position: particle.position,
+                velocity: particle.velocity,
+                size: emitter.start_size + (emitter.end_size - emitter.start_size) * t,
+                color: emitter.color_at(t),
+                texture: emitter.texture.as_deref(),
+            }
+        })
+    }
+}
+
+// Turns what happens in the game into particles: explosions for destroyed
+// asteroids and lost ships, a trail behind every rocket and exhaust while the
+// ship thrusts. Launch flashes come from fire_rocket.
+#[derive(Default)]
+pub struct ParticleEffects {
+    reader: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'a> System<'a> for ParticleEffects {
+    type SystemData = (
+        Read<'a, EventChannel<CollisionEvent>>,
+        ReadExpect<'a, AsteroidArchetypes>,
+        Read<'a, components::DeltaTime>,
+        ReadExpect<'a, InputState>,
+        ReadExpect<'a, PlayerMovement>,
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Velocity>,
+        ReadStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::Rocket>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Invulnerable>,
+        WriteExpect<'a, Particles>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<CollisionEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            events,
+            archetypes,
+            delta_time,
+            input,
+            movement,
+            positions,
+            velocities,
+            asteroids,
+            rockets,
+            players,
+            invulnerables,
+            mut particles,
+        ) = data;
+        let delta_time = delta_time.0;
+        particles.update(delta_time);
+
+        for event in events.read(
+            self.reader
+                .as_mut()
+                .expect("ParticleEffects was not set up"),
+        ) {
+            match event {
+                CollisionEvent::RocketHitAsteroid { asteroid, .. } => {
+                    if let (Some(position), Some(velocity), Some(tier)) = (
+                        positions.get(*asteroid),
+                        velocities.get(*asteroid),
+                        asteroids
+                            .get(*asteroid)
+                            .and_then(|asteroid| archetypes.tier(&asteroid.kind, asteroid.size)),
+                    ) {
+                        particles.burst(
+                            ASTEROID_EXPLOSION,
+                            position.pos,
+                            velocity.vel.heading(),
+                            tier.scale as f64,
+                        );
+                    }
+                }
+                CollisionEvent::PlayerHitAsteroid { player, .. }
+                    if can_be_hurt(&players, &invulnerables, *player) =>
+                {
+                    if let Some(position) = positions.get(*player) {
+                        particles.burst(SHIP_EXPLOSION, position.pos, position.rot, 1.0);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (_, position, velocity) in (&rockets, &positions, &velocities).join() {
+            particles.stream(
+                ROCKET_TRAIL,
+                position.pos,
+                velocity.vel.heading() + 180.0,
+                delta_time,
+            );
+        }
+        for (_, position) in (&players, &positions).join() {
+            // Exhaust goes out the opposite way to where the ship is pushed
+            if let Some(push) = game::push_heading(movement.control_scheme, &input, position) {
+                particles.stream(THRUSTER, position.pos, push + 180.0, delta_time);
+            }
+        }
+    }
+}
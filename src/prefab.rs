@@ -0,0 +1,295 @@
+use rand::Rng;
+use specs::{Builder, Entities, Entity, Join, LazyUpdate, LendJoin, World, WorldExt};
+
+use std::collections::HashMap;
+
+use crate::animation;
+use crate::archetypes::{AsteroidArchetypes, AsteroidTier};
+use crate::atlas::{self, Atlases};
+use crate::components;
+use crate::lives::LivesConfig;
+use crate::vector2D::Vector2D;
+
+pub const PLAYER: &str = "player";
+pub const ROCKET: &str = "rocket";
+pub const GAME_DATA: &str = "game_data";
+// What is left of the ship for its death clip.
+pub const SHIP_WRECK: &str = "ship_wreck";
+
+pub fn asteroid_name(kind: &str, size: u32) -> String {
+    format!("{}_{}", kind, size)
+}
+
+// Component values an entity starts with. The same type is used for overrides
+// when spawning, any component set there replaces the one from the template.
+#[derive(Clone, Default)]
+pub struct Prefab {
+    pub position: Option<components::Position>,
+    pub velocity: Option<components::Velocity>,
+    pub renderable: Option<components::Renderable>,
+    pub player: Option<components::Player>,
+    pub asteroid: Option<components::Asteroid>,
+    pub rocket: Option<components::Rocket>,
+    pub collider: Option<components::Collider>,
+    pub game_data: Option<components::GameData>,
+    pub boundary: Option<components::Boundary>,
+    pub invulnerable: Option<components::Invulnerable>,
+    pub animation: Option<components::Animation>,
+}
+
+impl Prefab {
+    // Works with both `World::create_entity` and `LazyUpdate::create_entity`,
+    // so templates can be spawned directly or from inside a system.
+    pub fn build<B: Builder>(&self, builder: B, overrides: Prefab) -> Entity {
+        builder
+            .maybe_with(overrides.position.or_else(|| self.position.clone()))
+            .maybe_with(overrides.velocity.or_else(|| self.velocity.clone()))
+            .maybe_with(overrides.renderable.or_else(|| self.renderable.clone()))
+            .maybe_with(overrides.player.or_else(|| self.player.clone()))
+            .maybe_with(overrides.asteroid.or_else(|| self.asteroid.clone()))
+            .maybe_with(overrides.rocket.or_else(|| self.rocket.clone()))
+            .maybe_with(overrides.collider.or_else(|| self.collider.clone()))
+            .maybe_with(overrides.game_data.or_else(|| self.game_data.clone()))
+            .maybe_with(overrides.boundary.or(self.boundary))
+            .maybe_with(overrides.invulnerable.or_else(|| self.invulnerable.clone()))
+            .maybe_with(overrides.animation.or_else(|| self.animation.clone()))
+            .build()
+    }
+}
+
+#[derive(Default)]
+pub struct PrefabRegistry {
+    prefabs: HashMap<String, Prefab>,
+}
+
+impl PrefabRegistry {
+    // The templates for everything the game spawns, asteroids get one per tier.
+    pub fn new(archetypes: &AsteroidArchetypes, atlases: &Atlases, lives: &LivesConfig) -> Self {
+        let mut registry = PrefabRegistry::default();
+        let ship_renderable = clip_renderable(
+            atlases,
+            animation::SHIP,
+            animation::IDLE,
+            1,
+            components::RenderLayer::Player,
+        );
+
+        registry.register(
+            PLAYER,
+            Prefab {
+                position: Some(components::Position {
+                    pos: Vector2D::new(50.0, 50.0),
+                    rot: 270.0,
+                }),
+                velocity: Some(components::Velocity {
+                    vel: Vector2D::new(0.0, 0.0),
+                }),
+                renderable: Some(ship_renderable.clone()),
+                player: Some(components::Player {
+                    can_take_damage: true,
+                }),
+                collider: Some(components::Collider { radius: 16.0 }),
+                boundary: Some(components::Boundary::Wrap),
+                animation: Some(components::Animation::new(animation::SHIP, animation::IDLE)),
+                ..Prefab::default()
+            },
+        );
+        registry.register(
+            SHIP_WRECK,
+            Prefab {
+                renderable: Some(ship_renderable),
+                animation: Some(components::Animation::new(
+                    animation::SHIP,
+                    animation::DEATH,
+                )),
+                ..Prefab::default()
+            },
+        );
+        registry.register(
+            ROCKET,
+            Prefab {
+                renderable: Some(components::Renderable {
+                    atlas: atlas::ROCKET.to_string(),
+                    sprite: atlas::ROCKET_SPRITE.to_string(),
+                    output_width: 40,
+                    output_height: 61,
+                    render_rotation: 0.0,
+                    layer: components::RenderLayer::Projectiles,
+                }),
+                rocket: Some(components::Rocket { speed: 600.0 }),
+                collider: Some(components::Collider { radius: 0.0 }),
+                boundary: Some(components::Boundary::Despawn),
+                ..Prefab::default()
+            },
+        );
+        registry.register(
+            GAME_DATA,
+            Prefab {
+                game_data: Some(components::GameData {
+                    score: 0,
+                    level: 1,
+                    lives: lives.starting_lives,
+                    extra_lives_awarded: 0,
+                    respawn_timer: None,
+                }),
+                ..Prefab::default()
+            },
+        );
+        for tier in archetypes.tiers.iter() {
+            registry.register(
+                &asteroid_name(&tier.kind, tier.size),
+                asteroid_prefab(tier, atlases),
+            );
+        }
+
+        registry
+    }
+
+    pub fn register(&mut self, name: &str, prefab: Prefab) {
+        self.prefabs.insert(name.to_string(), prefab);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.get(name)
+    }
+
+    pub fn spawn_lazy(
+        &self,
+        name: &str,
+        lazy: &LazyUpdate,
+        entities: &Entities,
+        overrides: Prefab,
+    ) -> Option<Entity> {
+        let prefab = self.get(name)?;
+        Some(prefab.build(lazy.create_entity(entities), overrides))
+    }
+}
+
+pub fn spawn(ecs: &mut World, name: &str, overrides: Prefab) -> Option<Entity> {
+    let prefab = ecs.read_resource::<PrefabRegistry>().get(name)?.clone();
+    Some(prefab.build(ecs.create_entity(), overrides))
+}
+
+// Points every Renderable and Animation at sprites and clips the atlases have,
+// for after the definitions were swapped while playing. Clips keep playing
+// where they were, anything the atlases lack now goes back to what the
+// entity's prefab starts with.
+pub fn resolve_sprites(world: &World) {
+    let entities = world.entities();
+    let prefabs = world.read_resource::<PrefabRegistry>();
+    let atlases = world.read_resource::<Atlases>();
+    let players = world.read_storage::<components::Player>();
+    let asteroids = world.read_storage::<components::Asteroid>();
+    let rockets = world.read_storage::<components::Rocket>();
+    let mut renderables = world.write_storage::<components::Renderable>();
+    let mut animations = world.write_storage::<components::Animation>();
+
+    for (entity, renderable, animation) in
+        (&entities, &mut renderables, (&mut animations).maybe()).join()
+    {
+        // Ship wrecks are the only other things that play a clip
+        let name = if players.contains(entity) {
+            PLAYER.to_string()
+        } else if let Some(asteroid) = asteroids.get(entity) {
+            asteroid_name(&asteroid.kind, asteroid.size)
+        } else if rockets.contains(entity) {
+            ROCKET.to_string()
+        } else if animation.is_some() {
+            SHIP_WRECK.to_string()
+        } else {
+            continue;
+        };
+        let prefab = prefabs.get(&name);
+
+        if let Some(animation) = animation {
+            if atlases.clip(&animation.set, &animation.clip).is_none() {
+                if let Some(start) = prefab.and_then(|prefab| prefab.animation.clone()) {
+                    *animation = start;
+                }
+            }
+            if let Some(clip) = atlases.clip(&animation.set, &animation.clip) {
+                animation.frame = animation.frame.min(clip.frames.len() - 1);
+                renderable.atlas.clone_from(&animation.set);
+                renderable.sprite = clip.frames[animation.frame].sprite.clone();
+            }
+        }
+        if atlases
+            .sprite(&renderable.atlas, &renderable.sprite)
+            .is_none()
+        {
+            if let Some(start) = prefab.and_then(|prefab| prefab.renderable.clone()) {
+                *renderable = components::Renderable {
+                    render_rotation: renderable.render_rotation,
+                    ..start
+                };
+            }
+        }
+    }
+}
+
+// Shows the first sprite of `clip`, drawn `scale` times the sprite's size.
+// Nothing shows if the atlas has no such clip.
+fn clip_renderable(
+    atlases: &Atlases,
+    atlas: &str,
+    clip: &str,
+    scale: u32,
+    layer: components::RenderLayer,
+) -> components::Renderable {
+    let sprite = atlases
+        .clip(atlas, clip)
+        .map(|clip| clip.frames[0].sprite.clone())
+        .unwrap_or_default();
+    let size = atlases.sprite(atlas, &sprite);
+    components::Renderable {
+        atlas: atlas.to_string(),
+        output_width: size.map_or(0, |size| size.width * scale),
+        output_height: size.map_or(0, |size| size.height * scale),
+        sprite,
+        render_rotation: 0.0,
+        layer,
+    }
+}
+
+fn asteroid_prefab(tier: &AsteroidTier, atlases: &Atlases) -> Prefab {
+    Prefab {
+        renderable: Some(clip_renderable(
+            atlases,
+            &tier.atlas,
+            animation::SPIN,
+            tier.scale,
+            components::RenderLayer::Asteroids,
+        )),
+        animation: Some(components::Animation::new(&tier.atlas, animation::SPIN)),
+        asteroid: Some(components::Asteroid {
+            rotation_speed: tier.spin,
+            kind: tier.kind.clone(),
+            size: tier.size,
+        }),
+        collider: Some(components::Collider {
+            radius: tier.hitbox_radius,
+        }),
+        boundary: Some(components::Boundary::Wrap),
+        ..Prefab::default()
+    }
+}
+
+// Overrides that put an asteroid of `tier` at `position`, moving along `heading`
+// at a speed picked from the tier's range.
+pub fn asteroid_overrides<R: Rng>(
+    tier: &AsteroidTier,
+    position: Vector2D,
+    heading: f64,
+    rng: &mut R,
+) -> Prefab {
+    Prefab {
+        position: Some(components::Position {
+            pos: position,
+            rot: heading,
+        }),
+        velocity: Some(components::Velocity {
+            vel: Vector2D::from_heading(heading, tier.random_speed(rng)),
+        }),
+        ..Prefab::default()
+    }
+}
@@ -0,0 +1,332 @@
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, WindowCanvas};
+use specs::{Join, LendJoin, World, WorldExt};
+
+use asteroidgame::atlas::Atlases;
+use asteroidgame::boundary::PlayField;
+use asteroidgame::camera::Camera;
+use asteroidgame::components::{self, RenderLayer};
+use asteroidgame::particles::Particles;
+use asteroidgame::vector2D::Vector2D;
+
+use crate::texture_manager::TextureManager;
+use crate::ui::{self, UIElement};
+
+// How often an invulnerable entity flashes.
+const BLINKS_PER_SECOND: f64 = 5.0;
+// Length of the minimap's longer side, and its distance from the top right
+// corner of the view.
+const MINIMAP_SIZE: f64 = 160.0;
+const MINIMAP_MARGIN: i32 = 10;
+// Room left above the minimap for the entity counter.
+const MINIMAP_TOP: i32 = 50;
+const BACKGROUND_COLOR: Color = Color::RGB(0, 0, 0);
+
+// What an entity is drawn from.
+type Drawable<'a> = (
+    &'a components::Renderable,
+    &'a components::Position,
+    Option<&'a components::PreviousPosition>,
+    Option<&'a components::Boundary>,
+);
+
+// Text and menus drawn over the world. `dim` darkens everything under them,
+// for menus shown on top of the game.
+#[derive(Default)]
+pub struct Hud<'h, 'a> {
+    pub elements: Vec<&'h UIElement<'a>>,
+    pub dim: bool,
+}
+
+// One thing to draw, sorted into its layer with everything else.
+enum Layered<'r> {
+    Background,
+    Entity(Drawable<'r>),
+    Particles,
+    Minimap,
+    Dim,
+    Ui(&'r UIElement<'r>),
+}
+
+impl<'r> Layered<'r> {
+    fn layer(&self) -> RenderLayer {
+        match self {
+            Layered::Background => RenderLayer::Background,
+            Layered::Entity((renderable, ..)) => renderable.layer,
+            Layered::Particles => RenderLayer::Effects,
+            Layered::Minimap | Layered::Dim | Layered::Ui(_) => RenderLayer::Hud,
+        }
+    }
+}
+
+// Blends the position from the start of the tick into the current one.
+// Entities that jumped more than half the field wrapped around, so they are
+// not blended.
+fn interpolate(
+    field: &PlayField,
+    previous: Option<&components::PreviousPosition>,
+    current: &components::Position,
+    alpha: f64,
+) -> Vector2D {
+    if let Some(previous) = previous {
+        let diff = current.pos.substract(previous.pos);
+        let half = field.center();
+        if diff.x.abs() < half.x && diff.y.abs() < half.y {
+            return previous.pos.add(diff.multiply(alpha));
+        }
+    }
+    current.pos
+}
+
+// Draws every renderable entity the camera can see with the HUD over them,
+// `alpha` is how far the frame is between the last tick and the next one.
+pub fn render_world(
+    canvas: &mut WindowCanvas,
+    texture_manager: &mut TextureManager,
+    ecs: &World,
+    alpha: f64,
+    hud: Hud,
+) -> Result<(), String> {
+    let positions = ecs.read_storage::<components::Position>();
+    let previous_positions = ecs.read_storage::<components::PreviousPosition>();
+    let renderables = ecs.read_storage::<components::Renderable>();
+    let boundaries = ecs.read_storage::<components::Boundary>();
+    let invulnerables = ecs.read_storage::<components::Invulnerable>();
+    let field = ecs.read_resource::<PlayField>();
+    let camera = ecs.read_resource::<Camera>();
+    let atlases = ecs.read_resource::<Atlases>();
+
+    let view_center = camera.interpolated_position(&field, alpha);
+    let (view_width, view_height) = camera.view_size();
+    let view = Rect::new(0, 0, view_width, view_height);
+    // Distance on screen between two copies of the field
+    let field_step = field.size().multiply(camera.zoom);
+
+    let mut layered = vec![Layered::Background];
+    layered.extend(
+        (
+            &renderables,
+            &positions,
+            (&previous_positions).maybe(),
+            boundaries.maybe(),
+            invulnerables.maybe(),
+        )
+            .join()
+            .filter(|(_, _, _, _, invulnerable)| match invulnerable {
+                Some(invulnerable) => {
+                    (invulnerable.remaining * BLINKS_PER_SECOND * 2.0) as i64 % 2 == 0
+                }
+                None => true,
+            })
+            .map(|(renderable, position, previous, boundary, _)| {
+                Layered::Entity((renderable, position, previous, boundary))
+            }),
+    );
+    layered.push(Layered::Particles);
+    if !camera.shows_whole_field(&field) {
+        layered.push(Layered::Minimap);
+    }
+    if hud.dim {
+        layered.push(Layered::Dim);
+    }
+    layered.extend(hud.elements.into_iter().map(Layered::Ui));
+    // A stable sort keeps everything in the order it was collected inside a
+    // layer, so the order is the same every frame
+    layered.sort_by_key(Layered::layer);
+
+    let draw_entity = |canvas: &mut WindowCanvas,
+                       texture_manager: &mut TextureManager,
+                       drawable: Drawable|
+     -> Result<(), String> {
+        let (renderable, position, previous, boundary) = drawable;
+        let pos = interpolate(&field, previous, position, alpha);
+        // Data reloaded while playing can leave an entity without anything to
+        // show for a moment, that is not worth stopping the game for
+        let found = atlases.atlases.get(&renderable.atlas).and_then(|atlas| {
+            let texture = texture_manager.get_texture(&atlas.texture).ok()?;
+            Some((texture, atlas.sprites.get(&renderable.sprite)?))
+        });
+        let Some((texture, sprite)) = found else {
+            texture_manager.report_missing_sprite(&renderable.atlas, &renderable.sprite);
+            return Ok(());
+        };
+        let src = Rect::new(
+            sprite.x as i32,
+            sprite.y as i32,
+            sprite.width,
+            sprite.height,
+        );
+        let width = ((renderable.output_width as f64 * camera.zoom).round() as u32).max(1);
+        let height = ((renderable.output_height as f64 * camera.zoom).round() as u32).max(1);
+        let mut draw_at = |center: Vector2D| {
+            let screen_rect = Rect::from_center(
+                Point::new(center.x.round() as i32, center.y.round() as i32),
+                width,
+                height,
+            );
+            if !screen_rect.has_intersection(view) {
+                return Ok(());
+            }
+            canvas.copy_ex(
+                texture,
+                src,
+                screen_rect,
+                renderable.render_rotation,
+                None,
+                false,
+                false,
+            )
+        };
+
+        // Something wrapping around is drawn at every copy of the field that
+        // shows on screen, so the part hanging over an edge shows on the other
+        // side and a small field repeats to fill the view
+        if boundary == Some(&components::Boundary::Wrap) {
+            let nearest = view_center.add(field.delta(view_center, pos, true));
+            let center = camera.world_to_screen(view_center, nearest);
+            for y in [-1.0, 0.0, 1.0] {
+                for x in [-1.0, 0.0, 1.0] {
+                    draw_at(center.add(Vector2D::new(x * field_step.x, y * field_step.y)))?;
+                }
+            }
+            Ok(())
+        } else {
+            draw_at(camera.world_to_screen(view_center, pos))
+        }
+    };
+
+    for item in layered {
+        match item {
+            Layered::Background => {
+                canvas.set_draw_color(BACKGROUND_COLOR);
+                canvas.fill_rect(None)?;
+            }
+            Layered::Entity(drawable) => draw_entity(canvas, texture_manager, drawable)?,
+            Layered::Particles => {
+                render_particles(canvas, texture_manager, ecs, view_center, alpha)?
+            }
+            Layered::Minimap => render_minimap(canvas, ecs, &field, &camera, view_center)?,
+            Layered::Dim => ui::dim(canvas)?,
+            Layered::Ui(element) => ui::render_ui(canvas, std::slice::from_ref(element))?,
+        }
+    }
+    Ok(())
+}
+
+// Particles are blended from where they were at the start of the tick like
+// entities are.
+fn render_particles(
+    canvas: &mut WindowCanvas,
+    texture_manager: &mut TextureManager,
+    ecs: &World,
+    view_center: Vector2D,
+    alpha: f64,
+) -> Result<(), String> {
+    let particles = ecs.read_resource::<Particles>();
+    let field = ecs.read_resource::<PlayField>();
+    let camera = ecs.read_resource::<Camera>();
+    let behind = (1.0 - alpha) * ecs.read_resource::<components::DeltaTime>().0;
+    let (view_width, view_height) = camera.view_size();
+    let view = Rect::new(0, 0, view_width, view_height);
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    for sprite in particles.sprites() {
+        let position = sprite.position.substract(sprite.velocity.multiply(behind));
+        let nearest = view_center.add(field.delta(view_center, position, true));
+        let center = camera.world_to_screen(view_center, nearest);
+        let size = ((sprite.size * camera.zoom).round() as u32).max(1);
+        let rect = Rect::from_center(
+            Point::new(center.x.round() as i32, center.y.round() as i32),
+            size,
+            size,
+        );
+        if !rect.has_intersection(view) {
+            continue;
+        }
+        let (red, green, blue, opacity) = sprite.color;
+        match sprite.texture {
+            Some(name) => {
+                let texture = texture_manager.get_texture_mut(name)?;
+                texture.set_color_mod(red, green, blue);
+                texture.set_alpha_mod(opacity);
+                let drawn = canvas.copy(texture, None, rect);
+                // Entities may share the texture, they are drawn untinted
+                texture.set_color_mod(255, 255, 255);
+                texture.set_alpha_mod(255);
+                drawn?;
+            }
+            None => {
+                canvas.set_draw_color(Color::RGBA(red, green, blue, opacity));
+                canvas.fill_rect(rect)?;
+            }
+        }
+    }
+    canvas.set_blend_mode(BlendMode::None);
+    Ok(())
+}
+
+// The whole field shrunk into a corner, with the ship, the asteroids and the
+// part the camera is showing.
+fn render_minimap(
+    canvas: &mut WindowCanvas,
+    ecs: &World,
+    field: &PlayField,
+    camera: &Camera,
+    view_center: Vector2D,
+) -> Result<(), String> {
+    let positions = ecs.read_storage::<components::Position>();
+    let players = ecs.read_storage::<components::Player>();
+    let asteroids = ecs.read_storage::<components::Asteroid>();
+
+    let view_center = field.wrap(view_center);
+    let scale = MINIMAP_SIZE / field.width.max(field.height) as f64;
+    let size = field.size().multiply(scale);
+    let (view_width, view_height) = camera.view_size();
+    let area = Rect::new(
+        view_width as i32 - size.x as i32 - MINIMAP_MARGIN,
+        MINIMAP_TOP,
+        size.x as u32,
+        size.y as u32,
+    );
+    let to_map = |point: Vector2D| {
+        Point::new(
+            area.x() + (point.x * scale) as i32,
+            area.y() + (point.y * scale) as i32,
+        )
+    };
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+    canvas.fill_rect(area)?;
+    canvas.set_clip_rect(area);
+
+    canvas.set_draw_color(Color::RGBA(160, 160, 160, 255));
+    let asteroid_points: Vec<Point> = (&asteroids, &positions)
+        .join()
+        .map(|(_, position)| to_map(position.pos))
+        .collect();
+    canvas.draw_points(asteroid_points.as_slice())?;
+
+    canvas.set_draw_color(Color::RGBA(0, 255, 0, 255));
+    for (_, position) in (&players, &positions).join() {
+        canvas.fill_rect(Rect::from_center(to_map(position.pos), 4, 4))?;
+    }
+
+    // The view, drawn again one field over when it runs off an edge
+    let seen = Vector2D::new(view_width as f64, view_height as f64).multiply(1.0 / camera.zoom);
+    canvas.set_draw_color(Color::RGBA(255, 255, 255, 160));
+    for offset in field.wrap_offsets(view_center, seen.x / 2.0, seen.y / 2.0) {
+        canvas.draw_rect(Rect::from_center(
+            to_map(view_center.add(offset)),
+            (seen.x * scale) as u32,
+            (seen.y * scale) as u32,
+        ))?;
+    }
+
+    canvas.set_clip_rect(None);
+    canvas.set_draw_color(Color::RGBA(255, 255, 255, 120));
+    canvas.draw_rect(area)?;
+    canvas.set_blend_mode(BlendMode::None);
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::components;
+use crate::movement::{ControlScheme, PlayerMovement};
+use crate::simulation::{InputSnapshot, Simulation, SimulationConfig};
+use crate::storage;
+
+// Bump whenever the replay layout changes, replays with another version are not
+// played.
+pub const REPLAY_VERSION: u32 = 1;
+// Ticks between world checksums in a recording.
+pub const CHECKSUM_INTERVAL: u32 = 60;
+
+// The same input held for `ticks` ticks in a row, so idle stretches and held
+// keys take one entry instead of one per tick.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct InputRun {
+    ticks: u32,
+    input: InputSnapshot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Checksum {
+    // Number of ticks run when the checksum was taken.
+    pub tick: u32,
+    pub value: u64,
+}
+
+// Everything needed to play a run again: the settings that change how the
+// simulation behaves, the seed and the input of every tick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    // Version of the game that made the recording.
+    pub build: String,
+    pub seed: u64,
+    pub tick_rate: u32,
+    pub control_scheme: String,
+    inputs: Vec<InputRun>,
+    checksums: Vec<Checksum>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read replay {}: {}", path.display(), e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let replay: Replay = ron::from_str(text).map_err(|e| e.to_string())?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!("unsupported replay version {}", replay.version));
+        }
+        if ControlScheme::from_name(&replay.control_scheme).is_none() {
+            return Err(format!(
+                "unknown control scheme {:?}",
+                replay.control_scheme
+            ));
+        }
+        Ok(replay)
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::to_string(self).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        storage::write_atomically(path, &self.to_ron()?)
+    }
+
+    // Length of the recording in ticks.
+    pub fn ticks(&self) -> u32 {
+        self.inputs.iter().map(|run| run.ticks).sum()
+    }
+
+    // Makes `config` match the one the recording was made with.
+    pub fn configure(&self, config: &mut SimulationConfig) {
+        config.seed = self.seed;
+        config.tick_rate = self.tick_rate;
+        if let Some(scheme) = ControlScheme::from_name(&self.control_scheme) {
+            config.player_movement.control_scheme = scheme;
+        }
+    }
+}
+
+// Where the front-end keeps the last game played.
+pub fn last_replay_path() -> Option<PathBuf> {
+    storage::data_file("last_replay.ron")
+}
+
+// Records a run from its first tick. Call `record` after every tick with the
+// input that tick was given.
+pub struct ReplayRecorder {
+    replay: Replay,
+    tick: u32,
+}
+
+impl ReplayRecorder {
+    // `simulation` has to be freshly started, input before this is not known.
+    pub fn new(simulation: &Simulation) -> Self {
+        let control_scheme = simulation
+            .world()
+            .read_resource::<PlayerMovement>()
+            .control_scheme;
+        ReplayRecorder {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                build: env!("CARGO_PKG_VERSION").to_string(),
+                seed: simulation.seed(),
+                tick_rate: simulation.tick_rate(),
+                control_scheme: control_scheme.name().to_string(),
+                inputs: Vec::new(),
+                checksums: Vec::new(),
+            },
+            tick: 0,
+        }
+    }
+
+    pub fn record(&mut self, input: &InputSnapshot, simulation: &Simulation) {
+        match self.replay.inputs.last_mut() {
+            Some(run) if run.input == *input => run.ticks += 1,
+            _ => self.replay.inputs.push(InputRun {
+                ticks: 1,
+                input: *input,
+            }),
+        }
+        self.tick += 1;
+        if self.tick.is_multiple_of(CHECKSUM_INTERVAL) {
+            self.replay.checksums.push(Checksum {
+                tick: self.tick,
+                value: checksum(simulation.world()),
+            });
+        }
+    }
+
+    // The recording so far, with a checksum of the final state so the end of
+    // a playback is always verified.
+    pub fn finish(mut self, simulation: &Simulation) -> Replay {
+        if !self.tick.is_multiple_of(CHECKSUM_INTERVAL) {
+            self.replay.checksums.push(Checksum {
+                tick: self.tick,
+                value: checksum(simulation.world()),
+            });
+        }
+        self.replay
+    }
+}
+
+// Feeds a recording back into a simulation one tick at a time.
+pub struct ReplayPlayer {
+    replay: Replay,
+    run: usize,
+    ticks_into_run: u32,
+    tick: u32,
+    next_checksum: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            run: 0,
+            ticks_into_run: 0,
+            tick: 0,
+            next_checksum: 0,
+        }
+    }
+
+    // Ticks played so far.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    // Runs the next recorded tick. Returns false once the recording is over,
+    // and an error when the world no longer matches the recorded checksum.
+    pub fn step(&mut self, simulation: &mut Simulation) -> Result<bool, String> {
+        let run = match self.replay.inputs.get(self.run) {
+            Some(run) => *run,
+            None => return Ok(false),
+        };
+        simulation.tick(&run.input);
+        self.tick += 1;
+        self.ticks_into_run += 1;
+        if self.ticks_into_run >= run.ticks {
+            self.run += 1;
+            self.ticks_into_run = 0;
+        }
+
+        if let Some(expected) = self.replay.checksums.get(self.next_checksum) {
+            if expected.tick == self.tick {
+                self.next_checksum += 1;
+                let actual = checksum(simulation.world());
+                if actual != expected.value {
+                    return Err(format!(
+                        "Replay desynced at tick {}: checksum {:016x}, recorded {:016x}",
+                        self.tick, actual, expected.value
+                    ));
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+// Plays a whole recording without a window, checking every checksum on the
+// way. Returns the simulation as the recording left it.
+pub fn play_headless(replay: &Replay, mut config: SimulationConfig) -> Result<Simulation, String> {
+    replay.configure(&mut config);
+    let mut simulation = Simulation::new(config);
+    let mut player = ReplayPlayer::new(replay.clone());
+    while player.step(&mut simulation)? {}
+    Ok(simulation)
+}
+
+// FNV-1a, picked over the standard hasher because its output is fixed, so
+// checksums stay comparable between builds and platforms.
+struct Fnv1a(u64);
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// Fingerprint of the state that decides how a run continues: where everything
+// is and where it is going, the score and lives, and how far the random number
+// generator has got.
+pub fn checksum(world: &World) -> u64 {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    let positions = world.read_storage::<components::Position>();
+    let velocities = world.read_storage::<components::Velocity>();
+    let gamedatas = world.read_storage::<components::GameData>();
+
+    (world.entities().join().count() as u64).hash(&mut hasher);
+    for (position, velocity) in (&positions, velocities.maybe()).join() {
+        position.pos.x.to_bits().hash(&mut hasher);
+        position.pos.y.to_bits().hash(&mut hasher);
+        position.rot.to_bits().hash(&mut hasher);
+        if let Some(velocity) = velocity {
+            velocity.vel.x.to_bits().hash(&mut hasher);
+            velocity.vel.y.to_bits().hash(&mut hasher);
+        }
+    }
+    for gamedata in gamedatas.join() {
+        gamedata.score.hash(&mut hasher);
+        gamedata.level.hash(&mut hasher);
+        gamedata.lives.hash(&mut hasher);
+    }
+    world
+        .read_resource::<components::GameRng>()
+        .get_word_pos()
+        .hash(&mut hasher);
+    hasher.finish()
+}
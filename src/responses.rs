@@ -0,0 +1,342 @@
+use specs::prelude::*;
+use specs::shrev::{Event, EventChannel, ReaderId};
+use specs::{Entities, Join};
+
+use crate::archetypes::AsteroidArchetypes;
+use crate::camera::{Camera, CameraConfig};
+use crate::collision::CollisionEvent;
+use crate::components;
+use crate::lives::LivesConfig;
+use crate::prefab::{self, asteroid_overrides, Prefab, PrefabRegistry};
+use crate::waves::WaveEvent;
+
+// Every system in here reacts to CollisionEvents. Each one keeps its own reader
+// so new reactions can be added next to these without touching detection:
+// scoring, splitting, deaths, effects (ScreenShake here and ParticleEffects in
+// particles.rs) and sound, which also announces the WaveEvents.
+
+// Whether a hit on `player` costs a life.
+pub(crate) fn can_be_hurt(
+    players: &ReadStorage<components::Player>,
+    invulnerables: &ReadStorage<components::Invulnerable>,
+    player: Entity,
+) -> bool {
+    players
+        .get(player)
+        .is_some_and(|player| player.can_take_damage)
+        && !invulnerables.contains(player)
+}
+
+fn register_reader<E: Event>(world: &mut World) -> ReaderId<E> {
+    world.fetch_mut::<EventChannel<E>>().register_reader()
+}
+
+#[derive(Default)]
+pub struct ScoreKeeper {
+    reader: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'a> System<'a> for ScoreKeeper {
+    type SystemData = (
+        Read<'a, EventChannel<CollisionEvent>>,
+        ReadExpect<'a, AsteroidArchetypes>,
+        ReadStorage<'a, components::Asteroid>,
+        WriteStorage<'a, components::GameData>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_reader(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (events, archetypes, asteroids, mut gamedatas) = data;
+        let mut score: u32 = 0;
+
+        for event in events.read(self.reader.as_mut().expect("ScoreKeeper was not set up")) {
+            if let CollisionEvent::RocketHitAsteroid { asteroid, .. } = event {
+                if let Some(tier) = asteroids
+                    .get(*asteroid)
+                    .and_then(|asteroid| archetypes.tier(&asteroid.kind, asteroid.size))
+                {
+                    score += tier.score;
+                }
+            }
+        }
+        for gamedata in (&mut gamedatas).join() {
+            gamedata.score += score;
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RocketImpact {
+    reader: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'a> System<'a> for RocketImpact {
+    type SystemData = (Read<'a, EventChannel<CollisionEvent>>, Entities<'a>);
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_reader(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (events, entities) = data;
+
+        for event in events.read(self.reader.as_mut().expect("RocketImpact was not set up")) {
+            if let CollisionEvent::RocketHitAsteroid { rocket, .. } = event {
+                entities.delete(*rocket).ok();
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AsteroidSplitter {
+    reader: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'a> System<'a> for AsteroidSplitter {
+    type SystemData = (
+        Read<'a, EventChannel<CollisionEvent>>,
+        ReadExpect<'a, AsteroidArchetypes>,
+        ReadExpect<'a, PrefabRegistry>,
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::Velocity>,
+        WriteExpect<'a, components::GameRng>,
+        Read<'a, LazyUpdate>,
+        Entities<'a>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_reader(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            events,
+            archetypes,
+            prefabs,
+            positions,
+            asteroids,
+            velocities,
+            mut rng,
+            lazy,
+            entities,
+        ) = data;
+
+        for event in events.read(
+            self.reader
+                .as_mut()
+                .expect("AsteroidSplitter was not set up"),
+        ) {
+            let asteroid_entity = match event {
+                CollisionEvent::RocketHitAsteroid { asteroid, .. } => *asteroid,
+                _ => continue,
+            };
+            let (asteroid_pos, asteroid, asteroid_velocity) = match (
+                positions.get(asteroid_entity),
+                asteroids.get(asteroid_entity),
+                velocities.get(asteroid_entity),
+            ) {
+                (Some(pos), Some(asteroid), Some(velocity)) => (pos, asteroid, velocity),
+                _ => continue,
+            };
+            entities.delete(asteroid_entity).ok();
+
+            let tier = match archetypes.tier(&asteroid.kind, asteroid.size) {
+                Some(tier) => tier,
+                None => continue,
+            };
+            let piece_tier = match tier
+                .splits_into
+                .and_then(|split| archetypes.tier(&tier.kind, split.size))
+            {
+                Some(piece_tier) => piece_tier,
+                None => continue,
+            };
+            let piece_name = prefab::asteroid_name(&piece_tier.kind, piece_tier.size);
+            for heading in tier.split_headings(asteroid_velocity.vel.heading()) {
+                prefabs.spawn_lazy(
+                    &piece_name,
+                    &lazy,
+                    &entities,
+                    asteroid_overrides(piece_tier, asteroid_pos.pos, heading, &mut *rng),
+                );
+            }
+        }
+    }
+}
+
+// Takes a life when the player is hit and starts the countdown to the next
+// ship, unless that was the last one.
+#[derive(Default)]
+pub struct PlayerDeath {
+    reader: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'a> System<'a> for PlayerDeath {
+    type SystemData = (
+        Read<'a, EventChannel<CollisionEvent>>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Invulnerable>,
+        ReadStorage<'a, components::Position>,
+        ReadStorage<'a, components::Renderable>,
+        WriteStorage<'a, components::GameData>,
+        ReadExpect<'a, LivesConfig>,
+        ReadExpect<'a, PrefabRegistry>,
+        Read<'a, LazyUpdate>,
+        Entities<'a>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_reader(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            events,
+            players,
+            invulnerables,
+            positions,
+            renderables,
+            mut gamedatas,
+            config,
+            prefabs,
+            lazy,
+            entities,
+        ) = data;
+
+        for event in events.read(self.reader.as_mut().expect("PlayerDeath was not set up")) {
+            if let CollisionEvent::PlayerHitAsteroid { player, .. } = event {
+                if can_be_hurt(&players, &invulnerables, *player) {
+                    entities.delete(*player).ok();
+                    // The wreck plays the death clip where the ship was, facing
+                    // the same way, and clears itself away
+                    prefabs.spawn_lazy(
+                        prefab::SHIP_WRECK,
+                        &lazy,
+                        &entities,
+                        Prefab {
+                            position: positions.get(*player).cloned(),
+                            renderable: renderables.get(*player).cloned(),
+                            ..Prefab::default()
+                        },
+                    );
+                    for gamedata in (&mut gamedatas).join() {
+                        gamedata.lives = gamedata.lives.saturating_sub(1);
+                        if gamedata.lives > 0 {
+                            gamedata.respawn_timer = Some(config.respawn_delay);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Shakes the screen a little for every asteroid hit and hard when a ship is lost.
+#[derive(Default)]
+pub struct ScreenShake {
+    reader: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'a> System<'a> for ScreenShake {
+    type SystemData = (
+        Read<'a, EventChannel<CollisionEvent>>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Invulnerable>,
+        ReadExpect<'a, CameraConfig>,
+        WriteExpect<'a, Camera>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_reader(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (events, players, invulnerables, config, mut camera) = data;
+
+        for event in events.read(self.reader.as_mut().expect("ScreenShake was not set up")) {
+            match event {
+                CollisionEvent::RocketHitAsteroid { .. } => camera.shake(config.hit_shake),
+                CollisionEvent::PlayerHitAsteroid { player, .. }
+                    if can_be_hurt(&players, &invulnerables, *player) =>
+                {
+                    camera.shake(config.death_shake)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Sounds the game asks for. Nothing plays them yet, a front-end with audio can
+// register a reader on the EventChannel and pick a sample for each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCue {
+    AsteroidDestroyed { size: u32 },
+    ShipDestroyed,
+    WaveStarted { wave: u32 },
+    WaveCleared { wave: u32 },
+}
+
+// Turns hits and WaveEvents into SoundCues.
+#[derive(Default)]
+pub struct SoundCues {
+    reader: Option<ReaderId<CollisionEvent>>,
+    wave_reader: Option<ReaderId<WaveEvent>>,
+}
+
+impl<'a> System<'a> for SoundCues {
+    type SystemData = (
+        Read<'a, EventChannel<CollisionEvent>>,
+        Read<'a, EventChannel<WaveEvent>>,
+        ReadStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Invulnerable>,
+        Write<'a, EventChannel<SoundCue>>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(register_reader(world));
+        self.wave_reader = Some(register_reader(world));
+    }
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (events, wave_events, asteroids, players, invulnerables, mut cues) = data;
+
+        for event in events.read(self.reader.as_mut().expect("SoundCues was not set up")) {
+            match event {
+                CollisionEvent::RocketHitAsteroid { asteroid, .. } => {
+                    if let Some(asteroid) = asteroids.get(*asteroid) {
+                        cues.single_write(SoundCue::AsteroidDestroyed {
+                            size: asteroid.size,
+                        });
+                    }
+                }
+                CollisionEvent::PlayerHitAsteroid { player, .. }
+                    if can_be_hurt(&players, &invulnerables, *player) =>
+                {
+                    cues.single_write(SoundCue::ShipDestroyed)
+                }
+                _ => {}
+            }
+        }
+        // The director runs after this, so a wave is announced the tick after
+        let wave_reader = self.wave_reader.as_mut().expect("SoundCues was not set up");
+        for event in wave_events.read(wave_reader) {
+            cues.single_write(match *event {
+                WaveEvent::Started { wave } => SoundCue::WaveStarted { wave },
+                WaveEvent::Cleared { wave } => SoundCue::WaveCleared { wave },
+            });
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use specs::saveload::{
+    DeserializeComponents, EntityData, Marker, MarkerAllocator, SerializeComponents, SimpleMarker,
+    SimpleMarkerAllocator,
+};
+use specs::{
+    Builder, Entities, Entity, Join, LendJoin, ReadStorage, World, WorldExt, WriteExpect,
+    WriteStorage,
+};
+
+use std::convert::Infallible;
+use std::path::PathBuf;
+
+use crate::archetypes;
+use crate::atlas::{self, Atlases};
+use crate::components::{
+    Animation, Asteroid, Boundary, Collider, GameData, GameRng, Invulnerable, Player, Position,
+    PreviousPosition, RenderLayer, Renderable, Rocket, Velocity,
+};
+use crate::input::InputState;
+use crate::prefab::{self, PrefabRegistry};
+use crate::storage;
+use crate::waves::WaveDirector;
+
+// Bump whenever the save layout changes. Older versions that can still be read
+// are upgraded while restoring, anything else is refused.
+pub const SAVE_VERSION: u32 = 6;
+
+// Marks entities that go into a save. Every entity is saved, markers are handed
+// out at save time so spawning code does not need to know about them.
+pub struct Saved;
+pub type SaveMarker = SimpleMarker<Saved>;
+pub type SaveMarkerAllocator = SimpleMarkerAllocator<Saved>;
+
+// Every component a running game uses, in the same order for both directions.
+type SavedStorages<'a> = (
+    ReadStorage<'a, Position>,
+    ReadStorage<'a, PreviousPosition>,
+    ReadStorage<'a, Velocity>,
+    ReadStorage<'a, Renderable>,
+    ReadStorage<'a, Player>,
+    ReadStorage<'a, Asteroid>,
+    ReadStorage<'a, Rocket>,
+    ReadStorage<'a, GameData>,
+    ReadStorage<'a, Collider>,
+    ReadStorage<'a, Boundary>,
+    ReadStorage<'a, Invulnerable>,
+    ReadStorage<'a, Animation>,
+);
+type RestoredStorages<'a> = (
+    WriteStorage<'a, Position>,
+    WriteStorage<'a, PreviousPosition>,
+    WriteStorage<'a, Velocity>,
+    WriteStorage<'a, Renderable>,
+    WriteStorage<'a, Player>,
+    WriteStorage<'a, Asteroid>,
+    WriteStorage<'a, Rocket>,
+    WriteStorage<'a, GameData>,
+    WriteStorage<'a, Collider>,
+    WriteStorage<'a, Boundary>,
+    WriteStorage<'a, Invulnerable>,
+    WriteStorage<'a, Animation>,
+);
+type SavedComponents = (
+    Option<Position>,
+    Option<PreviousPosition>,
+    Option<Velocity>,
+    Option<Renderable>,
+    Option<Player>,
+    Option<Asteroid>,
+    Option<Rocket>,
+    Option<GameData>,
+    Option<Collider>,
+    Option<Boundary>,
+    Option<Invulnerable>,
+    Option<Animation>,
+);
+// What saves before version 4 stored, before there were animations.
+type LegacyComponents = (
+    Option<Position>,
+    Option<PreviousPosition>,
+    Option<Velocity>,
+    Option<Renderable>,
+    Option<Player>,
+    Option<Asteroid>,
+    Option<Rocket>,
+    Option<GameData>,
+    Option<Collider>,
+    Option<Boundary>,
+    Option<Invulnerable>,
+);
+
+#[derive(Serialize, Deserialize)]
+struct SaveGame<C = SavedComponents> {
+    version: u32,
+    wave_director: WaveDirector,
+    // Kept so presses on the first tick after loading are worked out the same
+    // way they would have been without the break.
+    input: InputState,
+    // Missing from version 1 saves, which were made before the simulation had
+    // its own random number generator. Those carry on with a fresh one.
+    #[serde(default)]
+    rng: Option<GameRng>,
+    entities: Vec<EntityData<SaveMarker, C>>,
+}
+
+impl SaveGame<LegacyComponents> {
+    // The same save with the components it did not have left empty.
+    fn upgrade(self) -> SaveGame {
+        SaveGame {
+            version: self.version,
+            wave_director: self.wave_director,
+            input: self.input,
+            rng: self.rng,
+            entities: self
+                .entities
+                .into_iter()
+                .map(|data| {
+                    let (a, b, c, d, e, f, g, h, i, j, k) = data.components;
+                    EntityData {
+                        marker: data.marker,
+                        components: (a, b, c, d, e, f, g, h, i, j, k, None),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+// Read on its own first so a save from another version is reported as such
+// instead of as a parse error somewhere in the middle.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    storage::data_file("save.ron")
+}
+
+fn check_version(version: u32) -> Result<(), String> {
+    match version {
+        1..=SAVE_VERSION => Ok(()),
+        newer if newer > SAVE_VERSION => Err(format!(
+            "Save version {} is from a newer version of the game",
+            newer
+        )),
+        older => Err(format!("Save version {} is no longer supported", older)),
+    }
+}
+
+// Snapshots every entity and the resources that change while playing. Config
+// resources are not saved, they come from the data files when loading.
+pub fn save_world(world: &World) -> Result<String, String> {
+    {
+        let (entities, mut markers, mut allocator) = world.system_data::<(
+            Entities,
+            WriteStorage<SaveMarker>,
+            WriteExpect<SaveMarkerAllocator>,
+        )>();
+        for entity in (&entities).join() {
+            allocator.mark(entity, &mut markers);
+        }
+    }
+
+    let storages = world.system_data::<SavedStorages>();
+    let markers = world.read_storage::<SaveMarker>();
+    let ids = |entity| markers.get(entity).cloned();
+    let mut saved = Vec::new();
+    for (entity, marker) in (&world.entities(), &markers).join() {
+        let components: Result<SavedComponents, Infallible> =
+            storages.serialize_entity(entity, ids);
+        saved.push(EntityData {
+            marker: *marker,
+            components: components.unwrap_or_else(|never| match never {}),
+        });
+    }
+
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        wave_director: *world.read_resource::<WaveDirector>(),
+        input: *world.read_resource::<InputState>(),
+        rng: Some((*world.read_resource::<GameRng>()).clone()),
+        entities: saved,
+    };
+    ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())
+}
+
+// Replaces everything in `world` with the contents of a save. Nothing is
+// touched if the save cannot be read.
+pub fn restore_world(world: &mut World, text: &str) -> Result<(), String> {
+    let header: SaveHeader = ron::from_str(text).map_err(|e| e.to_string())?;
+    check_version(header.version)?;
+    let save: SaveGame = if header.version < 4 {
+        ron::from_str::<SaveGame<LegacyComponents>>(text)
+            .map_err(|e| e.to_string())?
+            .upgrade()
+    } else {
+        ron::from_str(text).map_err(|e| e.to_string())?
+    };
+    let rng = match save.rng {
+        Some(rng) => rng,
+        None if header.version == 1 => GameRng::seed_from_u64(rand::random()),
+        None => return Err("Save is missing the random number generator".to_string()),
+    };
+
+    world.delete_all();
+    world.maintain();
+    world.insert(save.wave_director);
+    world.insert(save.input);
+    world.insert(rng);
+    world.insert(SaveMarkerAllocator::new());
+
+    // Systems visit entities by index, giving the restored entities indices in
+    // the order they were saved keeps that order the same as before the save.
+    let mut restored: Vec<Entity> = save
+        .entities
+        .iter()
+        .map(|_| world.create_entity().build())
+        .collect();
+    restored.sort_by_key(|entity| entity.id());
+
+    {
+        let mut storages = world.system_data::<RestoredStorages>();
+        let mut markers = world.write_storage::<SaveMarker>();
+        let mut allocator = world.write_resource::<SaveMarkerAllocator>();
+        for (entity, data) in restored.into_iter().zip(save.entities) {
+            let marker = allocator.allocate(entity, Some(data.marker.id()));
+            markers.insert(entity, marker).map_err(|e| e.to_string())?;
+            let result: Result<(), Infallible> =
+                storages.deserialize_entity(entity, data.components, |_: SaveMarker| None);
+            result.unwrap_or_else(|never| match never {});
+        }
+    }
+    // Before the others, they look asteroids up by kind
+    if header.version < 6 {
+        assign_kinds(world);
+    }
+    if header.version < 3 {
+        assign_render_layers(world);
+    }
+    if header.version < 4 {
+        assign_animations(world);
+    }
+    if header.version < 5 {
+        assign_sprites(world);
+    }
+    Ok(())
+}
+
+// Saves before version 6 only had rocks.
+fn assign_kinds(world: &World) {
+    for asteroid in (&mut world.write_storage::<Asteroid>()).join() {
+        asteroid.kind = archetypes::ROCK.to_string();
+    }
+}
+
+// Saves before version 3 did not keep render layers, they follow from what
+// each entity is.
+fn assign_render_layers(world: &World) {
+    let entities = world.entities();
+    let players = world.read_storage::<Player>();
+    let rockets = world.read_storage::<Rocket>();
+    let asteroids = world.read_storage::<Asteroid>();
+    let mut renderables = world.write_storage::<Renderable>();
+
+    for (entity, renderable) in (&entities, &mut renderables).join() {
+        if players.contains(entity) {
+            renderable.layer = RenderLayer::Player;
+        } else if rockets.contains(entity) {
+            renderable.layer = RenderLayer::Projectiles;
+        } else if asteroids.contains(entity) {
+            renderable.layer = RenderLayer::Asteroids;
+        }
+    }
+}
+
+// Saves before version 4 had no animations, ships and asteroids get the ones
+// they would have been spawned with.
+fn assign_animations(world: &World) {
+    let entities = world.entities();
+    let prefabs = world.read_resource::<PrefabRegistry>();
+    let players = world.read_storage::<Player>();
+    let asteroids = world.read_storage::<Asteroid>();
+    let mut animations = world.write_storage::<Animation>();
+
+    for (entity, player, asteroid) in (&entities, players.maybe(), asteroids.maybe()).join() {
+        let name = match (player, asteroid) {
+            (Some(_), _) => prefab::PLAYER.to_string(),
+            (None, Some(asteroid)) => prefab::asteroid_name(&asteroid.kind, asteroid.size),
+            (None, None) => continue,
+        };
+        if let Some(animation) = prefabs
+            .get(&name)
+            .and_then(|prefab| prefab.animation.clone())
+        {
+            animations.insert(entity, animation).ok();
+        }
+    }
+}
+
+// Saves before version 5 picked sprites by texture and pixel rectangle. Anything
+// animated shows the sprite for its current frame, rockets their only one.
+fn assign_sprites(world: &World) {
+    let atlases = world.read_resource::<Atlases>();
+    let animations = world.read_storage::<Animation>();
+    let rockets = world.read_storage::<Rocket>();
+    let mut renderables = world.write_storage::<Renderable>();
+
+    for (renderable, animation, rocket) in
+        (&mut renderables, animations.maybe(), rockets.maybe()).join()
+    {
+        if let Some(animation) = animation {
+            if let Some(clip) = atlases.clip(&animation.set, &animation.clip) {
+                let frame = animation.frame.min(clip.frames.len() - 1);
+                renderable.atlas = animation.set.clone();
+                renderable.sprite = clip.frames[frame].sprite.clone();
+            }
+        } else if rocket.is_some() {
+            renderable.atlas = atlas::ROCKET.to_string();
+            renderable.sprite = atlas::ROCKET_SPRITE.to_string();
+        }
+    }
+}
@@ -0,0 +1,929 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+use specs::{Join, WorldExt};
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use asteroidgame::camera::Camera;
+use asteroidgame::components;
+use asteroidgame::highscores::{self, HighScoreEntry, HighScoreTable, MAX_INITIALS};
+use asteroidgame::input::{Action, Bindings, InputCollector};
+use asteroidgame::movement::PlayerMovement;
+use asteroidgame::replay::{self, Replay, ReplayPlayer, ReplayRecorder};
+use asteroidgame::simulation::{Simulation, SimulationConfig};
+use asteroidgame::storage;
+use asteroidgame::waves::WaveDirector;
+
+use crate::controls;
+use crate::renderer::{self, Hud};
+use crate::texture_manager::TextureManager;
+use crate::ui::{self, UIElement};
+
+// Longest frame we will try to catch up on, anything above this is dropped so a
+// stall does not make the simulation spend the next frames ticking non-stop.
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+const MENU_COLOR: Color = Color::RGBA(255, 255, 255, 255);
+const TITLE_COLOR: Color = Color::RGBA(255, 0, 0, 255);
+
+// What every scene gets to work with. The simulation lives here rather than in
+// the Playing scene so the pause and game-over screens can draw it too.
+pub struct Context<'a> {
+    pub simulation: Simulation,
+    pub texture_creator: &'a TextureCreator<WindowContext>,
+    pub texture_manager: TextureManager<'a>,
+    pub font: Font<'a, 'static>,
+    pub bindings: Bindings,
+    // Seed from the command line, every new game uses it when set.
+    pub seed: Option<u64>,
+    // Records the game in progress, None for games continued from a save since
+    // their start is not known.
+    pub recorder: Option<ReplayRecorder>,
+    pub high_scores: HighScoreTable,
+    // None when there is nowhere to keep them, scores then last until exit.
+    pub high_scores_path: Option<PathBuf>,
+    // Where a run is kept between sessions, None when there is nowhere to put it.
+    pub save_path: Option<PathBuf>,
+}
+
+impl<'a> Context<'a> {
+    // Width and height of the camera's view, which is also the size the UI is
+    // laid out in since the window scales it.
+    fn view_size(&self) -> (i32, i32) {
+        let (width, height) = self
+            .simulation
+            .world()
+            .read_resource::<Camera>()
+            .view_size();
+        (width as i32, height as i32)
+    }
+
+    // A line of text centered horizontally on the screen.
+    fn centered_text(
+        &self,
+        text: &str,
+        color: Color,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Result<UIElement<'a>, String> {
+        let (view_width, _) = self.view_size();
+        let position = Rect::from_center(Point::new(view_width / 2, y), width, height);
+        UIElement::text(&self.font, self.texture_creator, text, color, position)
+    }
+
+    // A line of text `height` pixels tall starting at `x`, as wide as the font
+    // makes it so rows of different lengths are not stretched.
+    fn text_line(
+        &self,
+        text: &str,
+        color: Color,
+        x: i32,
+        y: i32,
+        height: u32,
+    ) -> Result<UIElement<'a>, String> {
+        let (text_width, text_height) = self.font.size_of(text).map_err(|e| e.to_string())?;
+        let width = (text_width * height / text_height.max(1)).max(1);
+        UIElement::text(
+            &self.font,
+            self.texture_creator,
+            text,
+            color,
+            Rect::new(x, y, width, height),
+        )
+    }
+
+    // Stores the game recorded so far as the last replay.
+    pub fn finish_recording(&mut self) {
+        let recorder = match self.recorder.take() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        let replay = recorder.finish(&self.simulation);
+        let saved = replay::last_replay_path()
+            .ok_or_else(|| "No user data directory found".to_string())
+            .and_then(|path| replay.save(&path));
+        if let Err(e) = saved {
+            println!("Failed to save the replay: {}", e);
+        }
+    }
+
+    fn saved_game(&self) -> Option<&PathBuf> {
+        self.save_path.as_ref().filter(|path| path.exists())
+    }
+
+    fn final_score(&self) -> (u32, u32) {
+        self.simulation
+            .world()
+            .read_storage::<components::GameData>()
+            .join()
+            .map(|gamedata| (gamedata.score, gamedata.level))
+            .next()
+            .unwrap_or((0, 1))
+    }
+}
+
+pub enum Transition<'a> {
+    None,
+    Push(Box<dyn Scene<'a> + 'a>),
+    Pop,
+    Replace(Box<dyn Scene<'a> + 'a>),
+    // Drops every scene on the stack and starts over from this one.
+    Reset(Box<dyn Scene<'a> + 'a>),
+    Quit,
+}
+
+// Only the scene on top of the stack receives events, updates and draws. Scenes
+// shown over the game draw the world themselves.
+pub trait Scene<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String>;
+
+    fn update(
+        &mut self,
+        _ctx: &mut Context<'a>,
+        _frame_time: Duration,
+    ) -> Result<Transition<'a>, String> {
+        Ok(Transition::None)
+    }
+
+    fn render(&mut self, ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String>;
+
+    // Called when the scene above this one was popped.
+    fn resume(&mut self) {}
+}
+
+pub struct SceneStack<'a> {
+    scenes: Vec<Box<dyn Scene<'a> + 'a>>,
+}
+
+impl<'a> SceneStack<'a> {
+    pub fn new(first: Box<dyn Scene<'a> + 'a>) -> Self {
+        SceneStack {
+            scenes: vec![first],
+        }
+    }
+
+    pub fn top(&mut self) -> &mut (dyn Scene<'a> + 'a) {
+        self.scenes
+            .last_mut()
+            .expect("Scene stack is never empty")
+            .as_mut()
+    }
+
+    // Returns false once the game should close.
+    pub fn apply(&mut self, transition: Transition<'a>) -> bool {
+        match transition {
+            Transition::None => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+                match self.scenes.last_mut() {
+                    Some(scene) => scene.resume(),
+                    None => return false,
+                }
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            }
+            Transition::Reset(scene) => {
+                self.scenes.clear();
+                self.scenes.push(scene);
+            }
+            Transition::Quit => return false,
+        }
+        true
+    }
+}
+
+fn key_name(bindings: &Bindings, action: Action) -> &str {
+    bindings.key_for(action).unwrap_or("?")
+}
+
+// Each game gets its own seed unless one was given on the command line. It is
+// printed so a run can be reproduced with `--seed`.
+fn new_game<'a>(ctx: &mut Context<'a>) -> Box<dyn Scene<'a> + 'a> {
+    let seed = ctx.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}", seed);
+    ctx.simulation.restart(seed);
+    ctx.recorder = Some(ReplayRecorder::new(&ctx.simulation));
+    Box::new(Playing::new(ctx))
+}
+
+pub struct MainMenu<'a> {
+    ui_elements: Vec<UIElement<'a>>,
+}
+
+impl<'a> MainMenu<'a> {
+    pub fn new(ctx: &Context<'a>) -> Result<Self, String> {
+        let mut ui_elements = vec![
+            ctx.centered_text("Astroids", TITLE_COLOR, 160, 400, 100)?,
+            ctx.centered_text("Press Enter to start", MENU_COLOR, 300, 300, 40)?,
+        ];
+        if ctx.saved_game().is_some() {
+            ui_elements.push(ctx.centered_text("C to continue", MENU_COLOR, 360, 300, 40)?);
+        }
+        ui_elements.push(ctx.centered_text("H for high scores", MENU_COLOR, 420, 300, 40)?);
+        if replay::last_replay_path().is_some_and(|path| path.exists()) {
+            ui_elements.push(ctx.centered_text(
+                "W to watch last game",
+                MENU_COLOR,
+                480,
+                300,
+                40,
+            )?);
+        }
+        ui_elements.push(ctx.centered_text("Press Escape to quit", MENU_COLOR, 540, 300, 40)?);
+        Ok(MainMenu { ui_elements })
+    }
+
+    // Picks up the saved run. The save is removed once loaded so a run can only
+    // be continued once.
+    fn continue_game(ctx: &mut Context<'a>) -> Result<Transition<'a>, String> {
+        let path = match ctx.saved_game() {
+            Some(path) => path.clone(),
+            None => return Ok(Transition::None),
+        };
+        let restored = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| ctx.simulation.restore(&text));
+        if let Err(e) = restored {
+            println!("Cannot continue from {}: {}", path.display(), e);
+            return Ok(Transition::None);
+        }
+        std::fs::remove_file(&path).ok();
+        ctx.recorder = None;
+        Ok(Transition::Replace(Box::new(Playing::new(ctx))))
+    }
+}
+
+impl<'a> Scene<'a> for MainMenu<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String> {
+        Ok(match controls::menu_key(event) {
+            Some(Keycode::Return) => Transition::Replace(new_game(ctx)),
+            Some(Keycode::C) => Self::continue_game(ctx)?,
+            Some(Keycode::H) => Transition::Push(Box::new(HighScores::new(ctx, None, false)?)),
+            Some(Keycode::W) => match replay::last_replay_path().map(|path| Replay::load(&path)) {
+                Some(Ok(replay)) => Transition::Push(Box::new(ReplayPlayback::new(ctx, replay)?)),
+                Some(Err(e)) => {
+                    println!("{}", e);
+                    Transition::None
+                }
+                None => Transition::None,
+            },
+            Some(Keycode::Escape) => Transition::Quit,
+            _ => Transition::None,
+        })
+    }
+
+    fn render(&mut self, _ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String> {
+        ui::render_ui(canvas, &self.ui_elements)
+    }
+}
+
+pub struct Playing<'a> {
+    input: InputCollector,
+    accumulator: Duration,
+    alpha: f64,
+    ui_render_wait: u32,
+    ui_storage: Vec<UIElement<'a>>,
+    // "Wave N" banner shown while the next wave counts down, with the wave it
+    // was rendered for.
+    wave_banner: Option<(u32, UIElement<'a>)>,
+}
+
+impl<'a> Playing<'a> {
+    const MAX_UI_RENDER_WAIT: u32 = 100;
+
+    pub fn new(ctx: &Context<'a>) -> Self {
+        Playing {
+            input: InputCollector::new(ctx.bindings.gamepad),
+            accumulator: Duration::ZERO,
+            alpha: 0.0,
+            ui_render_wait: Self::MAX_UI_RENDER_WAIT,
+            ui_storage: Vec::new(),
+            wave_banner: None,
+        }
+    }
+
+    fn update_wave_banner(&mut self, ctx: &Context<'a>) -> Result<(), String> {
+        let director = *ctx.simulation.world().read_resource::<WaveDirector>();
+        if director.countdown().is_none() {
+            self.wave_banner = None;
+            return Ok(());
+        }
+        let next_wave = director.wave + 1;
+        if !matches!(self.wave_banner, Some((wave, _)) if wave == next_wave) {
+            let (_, view_height) = ctx.view_size();
+            let banner = ctx.centered_text(
+                &format!("Wave {}", next_wave),
+                TITLE_COLOR,
+                view_height / 3,
+                300,
+                80,
+            )?;
+            self.wave_banner = Some((next_wave, banner));
+        }
+        Ok(())
+    }
+
+    fn rebuild_ui(&mut self, ctx: &Context<'a>) -> Result<(), String> {
+        self.ui_storage.clear();
+        let world = ctx.simulation.world();
+        let (width, height) = ctx.view_size();
+        let text = |text: &str, color: Color, position: Rect| {
+            UIElement::text(&ctx.font, ctx.texture_creator, text, color, position)
+        };
+
+        for player in world.read_storage::<components::Player>().join() {
+            self.ui_storage.push(text(
+                &format!(
+                    "Press {} to toggle godmode : {}",
+                    key_name(&ctx.bindings, Action::ToggleGodMode),
+                    !player.can_take_damage
+                ),
+                Color::RGBA(0, 255, 0, 255),
+                Rect::new(10, height - 50, 300, 50),
+            )?);
+        }
+        for gamedata in world.read_storage::<components::GameData>().join() {
+            self.ui_storage.push(text(
+                &format!("Score: {}", gamedata.score),
+                Color::RGBA(255, 0, 0, 255),
+                Rect::new(10, 0, 100, 50),
+            )?);
+            self.ui_storage.push(text(
+                &format!("Level: {}", gamedata.level),
+                Color::RGBA(255, 0, 0, 255),
+                Rect::new(width / 2, 0, 100, 50),
+            )?);
+            self.ui_storage.push(text(
+                &format!("Lives: {}", gamedata.lives),
+                Color::RGBA(255, 0, 0, 255),
+                Rect::new(width / 4, 0, 100, 50),
+            )?);
+        }
+        self.ui_storage.push(text(
+            &format!("Entity amount: {}", world.entities().join().count()),
+            Color::RGBA(255, 0, 0, 255),
+            Rect::new(width - 200, 0, 200, 40),
+        )?);
+        self.ui_storage.push(text(
+            &format!(
+                "Press {} to spawn 20 enemies",
+                key_name(&ctx.bindings, Action::SpawnWave)
+            ),
+            Color::RGBA(0, 255, 0, 255),
+            Rect::new(width / 2, height - 50, 300, 50),
+        )?);
+        Ok(())
+    }
+}
+
+impl<'a> Scene<'a> for Playing<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String> {
+        if let Some(input_event) = controls::translate(event, &ctx.bindings) {
+            if input_event.pressed() == Some(Action::Pause) {
+                return Ok(Transition::Push(Box::new(Paused::new(ctx)?)));
+            }
+            self.input.handle(input_event);
+        }
+        Ok(Transition::None)
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut Context<'a>,
+        frame_time: Duration,
+    ) -> Result<Transition<'a>, String> {
+        let tick_duration = ctx.simulation.tick_duration();
+        self.accumulator += frame_time.min(MAX_FRAME_TIME);
+
+        while self.accumulator >= tick_duration {
+            let input = self.input.snapshot();
+            ctx.simulation.tick(&input);
+            if let Some(recorder) = &mut ctx.recorder {
+                recorder.record(&input, &ctx.simulation);
+            }
+            self.accumulator -= tick_duration;
+
+            if ctx.simulation.is_game_over() {
+                ctx.finish_recording();
+                let (score, _) = ctx.final_score();
+                if ctx.high_scores.qualifies(score) {
+                    return Ok(Transition::Replace(Box::new(EnterInitials::new(ctx)?)));
+                }
+                return Ok(Transition::Replace(Box::new(GameOver::new(ctx)?)));
+            }
+        }
+        self.alpha = self.accumulator.as_secs_f64() / tick_duration.as_secs_f64();
+        self.update_wave_banner(ctx)?;
+
+        self.ui_render_wait += 1;
+        if self.ui_render_wait >= Self::MAX_UI_RENDER_WAIT {
+            self.ui_render_wait = 0;
+            self.rebuild_ui(ctx)?;
+        }
+        Ok(Transition::None)
+    }
+
+    fn render(&mut self, ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String> {
+        let banner = self.wave_banner.as_ref().map(|(_, banner)| banner);
+        let hud = Hud {
+            elements: banner.into_iter().chain(&self.ui_storage).collect(),
+            dim: false,
+        };
+        renderer::render_world(
+            canvas,
+            &mut ctx.texture_manager,
+            ctx.simulation.world(),
+            self.alpha,
+            hud,
+        )
+    }
+
+    fn resume(&mut self) {
+        // Keys released while paused never reached us, start from a clean slate.
+        self.input.release_all();
+        self.ui_render_wait = Self::MAX_UI_RENDER_WAIT;
+    }
+}
+
+pub struct Paused<'a> {
+    ui_elements: Vec<UIElement<'a>>,
+}
+
+impl<'a> Paused<'a> {
+    pub fn new(ctx: &Context<'a>) -> Result<Self, String> {
+        Ok(Paused {
+            ui_elements: vec![
+                ctx.centered_text("Paused", TITLE_COLOR, 180, 300, 100)?,
+                ctx.centered_text("Escape to resume", MENU_COLOR, 320, 300, 40)?,
+                ctx.centered_text("R to restart", MENU_COLOR, 380, 300, 40)?,
+                ctx.centered_text("M for main menu", MENU_COLOR, 440, 300, 40)?,
+                ctx.centered_text("S to save and quit", MENU_COLOR, 500, 300, 40)?,
+            ],
+        })
+    }
+
+    fn save_and_quit(ctx: &mut Context<'a>) -> Result<Transition<'a>, String> {
+        let path = match &ctx.save_path {
+            Some(path) => path,
+            None => {
+                println!("No user data directory found, cannot save");
+                return Ok(Transition::None);
+            }
+        };
+        let saved = ctx
+            .simulation
+            .save()
+            .and_then(|text| storage::write_atomically(path, &text));
+        if let Err(e) = saved {
+            println!("Failed to save the game: {}", e);
+            return Ok(Transition::None);
+        }
+        ctx.finish_recording();
+        Ok(Transition::Reset(Box::new(MainMenu::new(ctx)?)))
+    }
+}
+
+impl<'a> Scene<'a> for Paused<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String> {
+        if controls::pressed_action(event, &ctx.bindings) == Some(Action::Pause) {
+            return Ok(Transition::Pop);
+        }
+        Ok(match controls::menu_key(event) {
+            Some(Keycode::Return) => Transition::Pop,
+            Some(Keycode::R) => {
+                ctx.finish_recording();
+                Transition::Reset(new_game(ctx))
+            }
+            Some(Keycode::M) => {
+                ctx.finish_recording();
+                Transition::Reset(Box::new(MainMenu::new(ctx)?))
+            }
+            Some(Keycode::S) => Self::save_and_quit(ctx)?,
+            _ => Transition::None,
+        })
+    }
+
+    fn render(&mut self, ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String> {
+        let hud = Hud {
+            elements: self.ui_elements.iter().collect(),
+            dim: true,
+        };
+        renderer::render_world(
+            canvas,
+            &mut ctx.texture_manager,
+            ctx.simulation.world(),
+            1.0,
+            hud,
+        )
+    }
+}
+
+pub struct GameOver<'a> {
+    ui_elements: Vec<UIElement<'a>>,
+}
+
+impl<'a> GameOver<'a> {
+    pub fn new(ctx: &Context<'a>) -> Result<Self, String> {
+        let (score, level) = ctx.final_score();
+
+        Ok(GameOver {
+            ui_elements: vec![
+                ctx.centered_text("Game Over", TITLE_COLOR, 160, 400, 100)?,
+                ctx.centered_text(&format!("Score: {}", score), MENU_COLOR, 280, 200, 50)?,
+                ctx.centered_text(&format!("Level: {}", level), MENU_COLOR, 340, 200, 50)?,
+                ctx.centered_text("Enter to play again", MENU_COLOR, 440, 300, 40)?,
+                ctx.centered_text("Escape for main menu", MENU_COLOR, 500, 300, 40)?,
+            ],
+        })
+    }
+}
+
+impl<'a> Scene<'a> for GameOver<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String> {
+        Ok(match controls::menu_key(event) {
+            Some(Keycode::Return) => Transition::Replace(new_game(ctx)),
+            Some(Keycode::Escape) => Transition::Replace(Box::new(MainMenu::new(ctx)?)),
+            _ => Transition::None,
+        })
+    }
+
+    fn render(&mut self, ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String> {
+        let hud = Hud {
+            elements: self.ui_elements.iter().collect(),
+            dim: true,
+        };
+        renderer::render_world(
+            canvas,
+            &mut ctx.texture_manager,
+            ctx.simulation.world(),
+            1.0,
+            hud,
+        )
+    }
+}
+
+// Asks for the player's initials after a game that made the high score table.
+// Letters are typed directly, or picked with up and down on a controller.
+pub struct EnterInitials<'a> {
+    initials: String,
+    ui_elements: Vec<UIElement<'a>>,
+    initials_text: UIElement<'a>,
+}
+
+impl<'a> EnterInitials<'a> {
+    pub fn new(ctx: &Context<'a>) -> Result<Self, String> {
+        let (score, _) = ctx.final_score();
+        Ok(EnterInitials {
+            initials: String::new(),
+            ui_elements: vec![
+                ctx.centered_text("New High Score", TITLE_COLOR, 140, 500, 100)?,
+                ctx.centered_text(&format!("Score: {}", score), MENU_COLOR, 250, 200, 50)?,
+                ctx.centered_text("Enter your initials", MENU_COLOR, 320, 300, 40)?,
+                ctx.centered_text("Enter to confirm", MENU_COLOR, 500, 300, 40)?,
+            ],
+            initials_text: Self::render_initials(ctx, "")?,
+        })
+    }
+
+    fn render_initials(ctx: &Context<'a>, initials: &str) -> Result<UIElement<'a>, String> {
+        let shown: String = initials
+            .chars()
+            .chain(std::iter::repeat('_'))
+            .take(MAX_INITIALS)
+            .collect();
+        ctx.centered_text(&shown, TITLE_COLOR, 410, 150, 70)
+    }
+
+    // Moves the last letter through the alphabet, for controllers.
+    fn cycle_letter(&mut self, step: u8) {
+        let last = self.initials.pop().unwrap_or('A');
+        let index = (last as u8).wrapping_sub(b'A') % 26;
+        self.initials.push((b'A' + (index + step) % 26) as char);
+    }
+
+    fn confirm(&self, ctx: &mut Context<'a>) -> Result<Transition<'a>, String> {
+        let (score, level) = ctx.final_score();
+        let initials = if self.initials.is_empty() {
+            "???".to_string()
+        } else {
+            self.initials.clone()
+        };
+        let control_scheme = ctx
+            .simulation
+            .world()
+            .read_resource::<PlayerMovement>()
+            .control_scheme;
+        let rank = ctx.high_scores.insert(HighScoreEntry {
+            initials,
+            score,
+            level,
+            date: highscores::today(),
+            mode: control_scheme.name().to_string(),
+        });
+        if let Some(path) = &ctx.high_scores_path {
+            if let Err(e) = ctx.high_scores.save(path) {
+                println!("Failed to save high scores: {}", e);
+            }
+        }
+        Ok(Transition::Replace(Box::new(HighScores::new(
+            ctx, rank, true,
+        )?)))
+    }
+}
+
+impl<'a> Scene<'a> for EnterInitials<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String> {
+        let key = match controls::menu_key(event) {
+            Some(key) => key,
+            None => return Ok(Transition::None),
+        };
+        let letter = key
+            .name()
+            .chars()
+            .next()
+            .filter(|c| key.name().len() == 1 && c.is_ascii_alphabetic());
+        match key {
+            Keycode::Return => return self.confirm(ctx),
+            Keycode::Escape => return Ok(Transition::Replace(Box::new(GameOver::new(ctx)?))),
+            Keycode::Backspace | Keycode::Left => {
+                self.initials.pop();
+            }
+            Keycode::Up => self.cycle_letter(1),
+            Keycode::Down => self.cycle_letter(25),
+            Keycode::Right if self.initials.len() < MAX_INITIALS => self.initials.push('A'),
+            _ => match letter {
+                Some(letter) if self.initials.len() < MAX_INITIALS => {
+                    self.initials.push(letter.to_ascii_uppercase())
+                }
+                _ => return Ok(Transition::None),
+            },
+        }
+        self.initials_text = Self::render_initials(ctx, &self.initials)?;
+        Ok(Transition::None)
+    }
+
+    fn render(&mut self, ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String> {
+        let hud = Hud {
+            elements: self
+                .ui_elements
+                .iter()
+                .chain([&self.initials_text])
+                .collect(),
+            dim: true,
+        };
+        renderer::render_world(
+            canvas,
+            &mut ctx.texture_manager,
+            ctx.simulation.world(),
+            1.0,
+            hud,
+        )
+    }
+}
+
+// The high score table, either opened from the main menu or shown after a game
+// with the new entry highlighted.
+pub struct HighScores<'a> {
+    ui_elements: Vec<UIElement<'a>>,
+    after_game: bool,
+}
+
+impl<'a> HighScores<'a> {
+    const ROW_HEIGHT: u32 = 30;
+
+    pub fn new(
+        ctx: &Context<'a>,
+        highlight: Option<usize>,
+        after_game: bool,
+    ) -> Result<Self, String> {
+        let mut ui_elements = vec![ctx.centered_text("High Scores", TITLE_COLOR, 70, 400, 80)?];
+
+        if ctx.high_scores.entries.is_empty() {
+            ui_elements.push(ctx.centered_text("No scores yet", MENU_COLOR, 280, 300, 40)?);
+        }
+        for (index, entry) in ctx.high_scores.entries.iter().enumerate() {
+            let color = if highlight == Some(index) {
+                TITLE_COLOR
+            } else {
+                MENU_COLOR
+            };
+            let row = format!(
+                "{:>2}. {:<3} {:>7}  L{:<3} {}  {}",
+                index + 1,
+                entry.initials,
+                entry.score,
+                entry.level,
+                entry.date,
+                entry.mode
+            );
+            let y = 140 + index as i32 * (Self::ROW_HEIGHT as i32 + 8);
+            ui_elements.push(ctx.text_line(&row, color, 80, y, Self::ROW_HEIGHT)?);
+        }
+
+        let hint = if after_game {
+            "Enter to play again, Escape for main menu"
+        } else {
+            "Escape to go back"
+        };
+        let (_, view_height) = ctx.view_size();
+        ui_elements.push(ctx.centered_text(hint, MENU_COLOR, view_height - 50, 500, 36)?);
+
+        Ok(HighScores {
+            ui_elements,
+            after_game,
+        })
+    }
+}
+
+impl<'a> Scene<'a> for HighScores<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String> {
+        Ok(match (controls::menu_key(event), self.after_game) {
+            (Some(Keycode::Return), true) => Transition::Replace(new_game(ctx)),
+            (Some(Keycode::Escape), true) => Transition::Replace(Box::new(MainMenu::new(ctx)?)),
+            (Some(Keycode::Return | Keycode::Escape), false) => Transition::Pop,
+            _ => Transition::None,
+        })
+    }
+
+    fn render(&mut self, ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String> {
+        if self.after_game {
+            let hud = Hud {
+                elements: self.ui_elements.iter().collect(),
+                dim: true,
+            };
+            return renderer::render_world(
+                canvas,
+                &mut ctx.texture_manager,
+                ctx.simulation.world(),
+                1.0,
+                hud,
+            );
+        }
+        ui::render_ui(canvas, &self.ui_elements)
+    }
+}
+
+// Plays a recorded game back in the window. Left and right change the speed,
+// the recording's checksums are checked as it goes.
+pub struct ReplayPlayback<'a> {
+    player: ReplayPlayer,
+    // The config the simulation had before, put back when leaving.
+    previous_config: SimulationConfig,
+    speed: u32,
+    accumulator: Duration,
+    alpha: f64,
+    speed_text: UIElement<'a>,
+    // Set once playback has stopped, saying why.
+    status: Option<UIElement<'a>>,
+}
+
+impl<'a> ReplayPlayback<'a> {
+    const MAX_SPEED: u32 = 16;
+
+    pub fn new(ctx: &mut Context<'a>, replay: Replay) -> Result<Self, String> {
+        if replay.build != env!("CARGO_PKG_VERSION") {
+            println!(
+                "Replay was recorded with version {}, it may not play back the same",
+                replay.build
+            );
+        }
+        let previous_config = ctx.simulation.config().clone();
+        let mut config = previous_config.clone();
+        replay.configure(&mut config);
+        ctx.simulation = Simulation::new(config);
+
+        Ok(ReplayPlayback {
+            player: ReplayPlayer::new(replay),
+            previous_config,
+            speed: 1,
+            accumulator: Duration::ZERO,
+            alpha: 0.0,
+            speed_text: Self::render_speed(ctx, 1)?,
+            status: None,
+        })
+    }
+
+    fn render_speed(ctx: &Context<'a>, speed: u32) -> Result<UIElement<'a>, String> {
+        ctx.text_line(
+            &format!("Replay x{}", speed),
+            Color::RGBA(0, 255, 0, 255),
+            10,
+            10,
+            30,
+        )
+    }
+
+    fn stop(&mut self, ctx: &Context<'a>, message: &str) -> Result<(), String> {
+        let (_, view_height) = ctx.view_size();
+        self.status = Some(ctx.centered_text(message, TITLE_COLOR, view_height / 3, 500, 60)?);
+        Ok(())
+    }
+}
+
+impl<'a> Scene<'a> for ReplayPlayback<'a> {
+    fn handle_event(
+        &mut self,
+        ctx: &mut Context<'a>,
+        event: &Event,
+    ) -> Result<Transition<'a>, String> {
+        let speed = match controls::menu_key(event) {
+            Some(Keycode::Return | Keycode::Escape) => {
+                // Definitions reloaded during playback stay in use
+                let mut config = self.previous_config.clone();
+                config.set_definitions(&ctx.simulation.config().definitions());
+                ctx.simulation = Simulation::new(config);
+                return Ok(Transition::Pop);
+            }
+            Some(Keycode::Right) => (self.speed * 2).min(Self::MAX_SPEED),
+            Some(Keycode::Left) => (self.speed / 2).max(1),
+            _ => return Ok(Transition::None),
+        };
+        if speed != self.speed {
+            self.speed = speed;
+            self.speed_text = Self::render_speed(ctx, speed)?;
+        }
+        Ok(Transition::None)
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut Context<'a>,
+        frame_time: Duration,
+    ) -> Result<Transition<'a>, String> {
+        if self.status.is_some() {
+            return Ok(Transition::None);
+        }
+        let tick_duration = ctx.simulation.tick_duration();
+        self.accumulator += frame_time.min(MAX_FRAME_TIME) * self.speed;
+
+        while self.accumulator >= tick_duration {
+            self.accumulator -= tick_duration;
+            match self.player.step(&mut ctx.simulation) {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.stop(ctx, "Replay finished")?;
+                    break;
+                }
+                Err(e) => {
+                    println!("{}", e);
+                    self.stop(ctx, &format!("Desync at tick {}", self.player.tick()))?;
+                    break;
+                }
+            }
+        }
+        self.alpha = self.accumulator.as_secs_f64() / tick_duration.as_secs_f64();
+        Ok(Transition::None)
+    }
+
+    fn render(&mut self, ctx: &mut Context<'a>, canvas: &mut WindowCanvas) -> Result<(), String> {
+        let hud = Hud {
+            elements: [&self.speed_text].into_iter().chain(&self.status).collect(),
+            dim: false,
+        };
+        renderer::render_world(
+            canvas,
+            &mut ctx.texture_manager,
+            ctx.simulation.world(),
+            self.alpha,
+            hud,
+        )
+    }
+}
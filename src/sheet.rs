@@ -0,0 +1,173 @@
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::animation::PlayMode;
+use crate::atlas::{AnimationFrame, Clip, SpriteRect};
+
+// How long a frame stays up when the sheet does not say, in seconds.
+const DEFAULT_FRAME_DURATION: f64 = 0.1;
+
+#[derive(Deserialize)]
+struct SheetRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct SheetFrame {
+    frame: SheetRect,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default)]
+    trimmed: bool,
+    // Milliseconds, only written by Aseprite.
+    duration: Option<u32>,
+}
+
+// Frames in the order the file lists them, which frame tags count by.
+struct OrderedFrames(Vec<(String, SheetFrame)>);
+
+impl<'de> Deserialize<'de> for OrderedFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = OrderedFrames;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of frames by name")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    frames.push(entry);
+                }
+                Ok(OrderedFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_map(FramesVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct FrameTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default = "forward")]
+    direction: String,
+    // Times the tag plays, missing or "0" for forever.
+    repeat: Option<String>,
+}
+
+fn forward() -> String {
+    String::from("forward")
+}
+
+#[derive(Default, Deserialize)]
+struct SheetMeta {
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<FrameTag>,
+}
+
+#[derive(Deserialize)]
+struct SheetFile {
+    frames: OrderedFrames,
+    #[serde(default)]
+    meta: SheetMeta,
+}
+
+// Sprites and clips read from a sprite sheet exported as a JSON hash, the way
+// TexturePacker and Aseprite write them. Frames become sprites under their
+// name, Aseprite's frame tags become clips.
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    pub sprites: BTreeMap<String, SpriteRect>,
+    pub clips: BTreeMap<String, Clip>,
+}
+
+impl Sheet {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read sprite sheet {}: {}", path, e))?;
+        Self::from_json(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let file: SheetFile = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let mut sheet = Sheet::default();
+        let mut durations = Vec::new();
+        for (name, frame) in file.frames.0.iter() {
+            // Neither can be drawn without keeping more than a rectangle
+            if frame.rotated || frame.trimmed {
+                return Err(format!(
+                    "Frame {:?} is rotated or trimmed, export the sheet without either",
+                    name
+                ));
+            }
+            let rect = &frame.frame;
+            sheet.sprites.insert(
+                name.clone(),
+                SpriteRect {
+                    x: rect.x,
+                    y: rect.y,
+                    width: rect.w,
+                    height: rect.h,
+                },
+            );
+            durations.push(
+                frame
+                    .duration
+                    .map_or(DEFAULT_FRAME_DURATION, |ms| ms as f64 / 1000.0),
+            );
+        }
+        if sheet.sprites.len() != file.frames.0.len() {
+            return Err("Frame names have to be unique".to_string());
+        }
+
+        for tag in file.meta.frame_tags.iter() {
+            if tag.from > tag.to || tag.to >= file.frames.0.len() {
+                return Err(format!(
+                    "Frame tag {:?} runs past the {} frames",
+                    tag.name,
+                    file.frames.0.len()
+                ));
+            }
+            let mut frames: Vec<AnimationFrame> = (tag.from..=tag.to)
+                .map(|index| AnimationFrame {
+                    sprite: file.frames.0[index].0.clone(),
+                    duration: durations[index],
+                })
+                .collect();
+            let mode = match tag.direction.as_str() {
+                "forward" | "reverse" if tag.repeat.as_deref() == Some("1") => PlayMode::Once,
+                "forward" | "reverse" => PlayMode::Loop,
+                "pingpong" | "pingpong_reverse" => PlayMode::PingPong,
+                other => {
+                    return Err(format!(
+                        "Frame tag {:?} has unknown direction {:?}",
+                        tag.name, other
+                    ))
+                }
+            };
+            if tag.direction.ends_with("reverse") {
+                frames.reverse();
+            }
+            if sheet
+                .clips
+                .insert(tag.name.clone(), Clip { mode, frames })
+                .is_some()
+            {
+                return Err(format!("Frame tag {:?} is there twice", tag.name));
+            }
+        }
+        Ok(sheet)
+    }
+}
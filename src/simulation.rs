@@ -0,0 +1,353 @@
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use specs::{Dispatcher, DispatcherBuilder, Join, World, WorldExt};
+
+use std::time::Duration;
+
+use crate::animation;
+use crate::archetypes::AsteroidArchetypes;
+use crate::assets::{self, AssetManifest, ASSET_MANIFEST_PATH};
+use crate::atlas::Atlases;
+use crate::boundary::PlayField;
+use crate::camera::{self, Camera, CameraConfig};
+use crate::input::{ActionSet, Aim, InputState};
+use crate::lives::LivesConfig;
+use crate::movement::PlayerMovement;
+use crate::particles::{ParticleConfig, ParticleEffects, Particles};
+use crate::prefab::{self, PrefabRegistry};
+use crate::save::{self, SaveMarker, SaveMarkerAllocator};
+use crate::waves::{WaveConfig, WaveDirector};
+use crate::{
+    asteroid, boundary, broadphase, collision, components, game, lives, responses, rocket, waves,
+};
+
+// Default number of simulation ticks per second. Speeds are in pixels per
+// second, so changing the rate changes precision rather than game speed.
+pub const TICK_RATE: u32 = 60;
+
+// Everything the game needs to know about the player's input for a single tick.
+// The front-end fills this in from whatever device it reads, so the simulation
+// never has to know about SDL.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InputSnapshot {
+    // Actions held down during the tick, presses and releases are worked out
+    // by comparing with the previous snapshot.
+    pub actions: ActionSet,
+    pub aim: Aim,
+}
+
+// Everything a Simulation is built from. `load` reads the data files listed in
+// the asset manifest and fills the rest with defaults, callers can then adjust
+// it.
+#[derive(Clone)]
+pub struct SimulationConfig {
+    pub tick_rate: u32,
+    // Seed for the first run, picked at random by `load`.
+    pub seed: u64,
+    pub play_field: PlayField,
+    pub camera: CameraConfig,
+    pub asteroid_archetypes: AsteroidArchetypes,
+    pub atlases: Atlases,
+    pub player_movement: PlayerMovement,
+    pub lives: LivesConfig,
+    pub waves: WaveConfig,
+    pub particles: ParticleConfig,
+}
+
+impl SimulationConfig {
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(&AssetManifest::load(ASSET_MANIFEST_PATH)?)
+    }
+
+    pub fn load_from(manifest: &AssetManifest) -> Result<Self, String> {
+        let waves_path = manifest.data(assets::WAVES)?;
+        let waves = WaveConfig::load(waves_path)?;
+        let play_field = PlayField::load(manifest.data(assets::PLAY_FIELD)?)?;
+        waves
+            .check_field(&play_field)
+            .map_err(|e| format!("{}: {}", waves_path, e))?;
+        let definitions = Definitions::load(manifest, &waves)?;
+        Ok(SimulationConfig {
+            tick_rate: TICK_RATE,
+            seed: rand::random(),
+            play_field,
+            camera: CameraConfig::load(manifest.data(assets::CAMERA)?)?,
+            asteroid_archetypes: definitions.asteroid_archetypes,
+            atlases: definitions.atlases,
+            player_movement: PlayerMovement::load(manifest.data(assets::MOVEMENT)?)?,
+            lives: LivesConfig::load(manifest.data(assets::LIVES)?)?,
+            waves,
+            particles: definitions.particles,
+        })
+    }
+
+    pub fn definitions(&self) -> Definitions {
+        Definitions {
+            asteroid_archetypes: self.asteroid_archetypes.clone(),
+            atlases: self.atlases.clone(),
+            particles: self.particles.clone(),
+        }
+    }
+
+    pub fn set_definitions(&mut self, definitions: &Definitions) {
+        self.asteroid_archetypes = definitions.asteroid_archetypes.clone();
+        self.atlases = definitions.atlases.clone();
+        self.particles = definitions.particles.clone();
+    }
+}
+
+// The part of the config that can be swapped while playing.
+#[derive(Clone)]
+pub struct Definitions {
+    pub asteroid_archetypes: AsteroidArchetypes,
+    pub atlases: Atlases,
+    pub particles: ParticleConfig,
+}
+
+impl Definitions {
+    // Reads the asteroid, atlas and particle files and checks them against
+    // each other, the asset manifest and the `waves` they are used with.
+    pub fn load(manifest: &AssetManifest, waves: &WaveConfig) -> Result<Self, String> {
+        let archetypes_path = manifest.data(assets::ASTEROIDS)?;
+        let asteroid_archetypes = AsteroidArchetypes::load(archetypes_path)?;
+        let atlases_path = manifest.data(assets::ATLASES)?;
+        let atlases = Atlases::load(atlases_path, manifest)?;
+        atlases
+            .check_textures(manifest)
+            .map_err(|e| format!("{}: {}", atlases_path, e))?;
+        asteroid_archetypes
+            .check_atlases(&atlases)
+            .map_err(|e| format!("{}: {}", archetypes_path, e))?;
+        let waves_path = manifest.data(assets::WAVES)?;
+        waves
+            .check_sizes(&asteroid_archetypes)
+            .map_err(|e| format!("{}: {}", waves_path, e))?;
+        let particles_path = manifest.data(assets::PARTICLES)?;
+        let particles = ParticleConfig::load(particles_path)?;
+        particles
+            .check_textures(manifest)
+            .map_err(|e| format!("{}: {}", particles_path, e))?;
+        Ok(Definitions {
+            asteroid_archetypes,
+            atlases,
+            particles,
+        })
+    }
+}
+
+pub struct Simulation {
+    ecs: World,
+    dispatcher: Dispatcher<'static, 'static>,
+    // Kept so a restart builds the world exactly the way `new` did.
+    config: SimulationConfig,
+}
+
+impl Simulation {
+    pub fn new(mut config: SimulationConfig) -> Self {
+        config.tick_rate = config.tick_rate.max(1);
+        let mut ecs = World::new();
+
+        ecs.register::<components::Position>();
+        ecs.register::<components::PreviousPosition>();
+        ecs.register::<components::Velocity>();
+        ecs.register::<components::Renderable>();
+        ecs.register::<components::Player>();
+        ecs.register::<components::Asteroid>();
+        ecs.register::<components::Rocket>();
+        ecs.register::<components::GameData>();
+        ecs.register::<components::Collider>();
+        ecs.register::<components::Boundary>();
+        ecs.register::<components::Invulnerable>();
+        ecs.register::<components::Animation>();
+        ecs.register::<SaveMarker>();
+
+        ecs.insert(components::DeltaTime(1.0 / config.tick_rate as f64));
+        ecs.insert(components::GameRng::seed_from_u64(config.seed));
+        ecs.insert(PrefabRegistry::new(
+            &config.asteroid_archetypes,
+            &config.atlases,
+            &config.lives,
+        ));
+        ecs.insert(config.asteroid_archetypes.clone());
+        ecs.insert(config.atlases.clone());
+        ecs.insert(config.player_movement.clone());
+        ecs.insert(config.lives.clone());
+        ecs.insert(WaveDirector::new(config.waves.countdown));
+        ecs.insert(config.waves.clone());
+        ecs.insert(config.play_field);
+        ecs.insert(Camera::new(&config.camera, &config.play_field));
+        ecs.insert(config.camera.clone());
+        ecs.insert(broadphase::SpatialGrid::for_field(&config.play_field));
+        ecs.insert(InputState::default());
+        ecs.insert(Particles::new(&config.particles, config.seed));
+        ecs.insert(SaveMarkerAllocator::new());
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(asteroid::AsteroidMover, "asteroid_mover", &[])
+            .with(rocket::RocketMover, "rocket_mover", &[])
+            .with(
+                boundary::BoundaryEnforcer,
+                "boundary",
+                &["asteroid_mover", "rocket_mover"],
+            )
+            .with(broadphase::BroadphaseBuilder, "broadphase", &["boundary"])
+            .with(
+                collision::CollisionDetector,
+                "collision_detector",
+                &["broadphase"],
+            )
+            .with(
+                responses::ScoreKeeper::default(),
+                "score_keeper",
+                &["collision_detector"],
+            )
+            .with(lives::ExtraLives, "extra_lives", &["score_keeper"])
+            .with(
+                responses::RocketImpact::default(),
+                "rocket_impact",
+                &["collision_detector"],
+            )
+            .with(
+                responses::AsteroidSplitter::default(),
+                "asteroid_splitter",
+                &["collision_detector"],
+            )
+            // After extra lives, so a life earned on the tick the last ship
+            // is lost brings the next one in instead of ending the run
+            .with(
+                responses::PlayerDeath::default(),
+                "player_death",
+                &["collision_detector", "extra_lives"],
+            )
+            .with(
+                responses::ScreenShake::default(),
+                "screen_shake",
+                &["collision_detector"],
+            )
+            .with(
+                responses::SoundCues::default(),
+                "sound_cues",
+                &["collision_detector"],
+            )
+            .with(
+                ParticleEffects::default(),
+                "particle_effects",
+                &["collision_detector"],
+            )
+            .with(
+                lives::InvulnerabilityTimer,
+                "invulnerability_timer",
+                &["player_death"],
+            )
+            .with(lives::Respawner, "respawner", &["player_death"])
+            .with(
+                waves::WaveDirectorSystem,
+                "wave_director",
+                &["asteroid_splitter", "respawner"],
+            )
+            .with(animation::Animator, "animator", &[])
+            .with(
+                animation::WreckRemover::default(),
+                "wreck_remover",
+                &["animator"],
+            )
+            .build();
+        dispatcher.setup(&mut ecs);
+
+        game::load_world(&mut ecs);
+        camera::snap_to_player(&ecs);
+
+        Simulation {
+            ecs,
+            dispatcher,
+            config,
+        }
+    }
+
+    pub fn tick_rate(&self) -> u32 {
+        self.config.tick_rate
+    }
+
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.config.tick_rate as f64)
+    }
+
+    // Seed the current run started from.
+    pub fn seed(&self) -> u64 {
+        self.config.seed
+    }
+
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    pub fn tick(&mut self, input: &InputSnapshot) {
+        game::store_previous_positions(&self.ecs);
+        self.ecs.write_resource::<InputState>().update(input);
+        game::update_player(&self.ecs);
+        camera::update_camera(&self.ecs);
+        game::update(&mut self.ecs);
+        game::update_player_rotation(&mut self.ecs, input.aim);
+        self.dispatcher.dispatch(&self.ecs);
+        self.ecs.maintain();
+    }
+
+    // The run is over once the last ship is lost. The world is left as it was
+    // so the front-end can still show the final score before restarting.
+    pub fn is_game_over(&self) -> bool {
+        let gamedatas = self.ecs.read_storage::<components::GameData>();
+        self.ecs.read_storage::<components::Player>().is_empty()
+            && gamedatas
+                .join()
+                .all(|gamedata| gamedata.respawn_timer.is_none())
+    }
+
+    // Throws the world away and starts a fresh run with the same config,
+    // drawing its random numbers from `seed`. Rebuilding rather than clearing
+    // means a restarted run plays out exactly like one from `new`.
+    pub fn restart(&mut self, seed: u64) {
+        let mut config = self.config.clone();
+        config.seed = seed;
+        *self = Simulation::new(config);
+    }
+
+    // The run so far as text, to be handed back to `restore` by a simulation
+    // built from the same config.
+    pub fn save(&self) -> Result<String, String> {
+        save::save_world(&self.ecs)
+    }
+
+    // Continues a saved run in place of the current one.
+    pub fn restore(&mut self, text: &str) -> Result<(), String> {
+        save::restore_world(&mut self.ecs, text)?;
+        self.ecs.write_resource::<Particles>().clear();
+        camera::snap_to_player(&self.ecs);
+        Ok(())
+    }
+
+    // Swaps in new asteroid, atlas and particle definitions, for trying out
+    // changes to them without restarting. Asteroids already in play keep their
+    // size and hitbox, live particles are dropped and sprites or clips that are
+    // gone are replaced by the ones from the prefabs.
+    pub fn reload_definitions(&mut self, definitions: &Definitions) {
+        self.config.set_definitions(definitions);
+        self.ecs.insert(PrefabRegistry::new(
+            &self.config.asteroid_archetypes,
+            &self.config.atlases,
+            &self.config.lives,
+        ));
+        self.ecs.insert(self.config.asteroid_archetypes.clone());
+        self.ecs.insert(self.config.atlases.clone());
+        self.ecs
+            .insert(Particles::new(&self.config.particles, self.config.seed));
+        prefab::resolve_sprites(&self.ecs);
+    }
+
+    pub fn world(&self) -> &World {
+        &self.ecs
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.ecs
+    }
+}
@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+
+// Where per-user game data lives on this platform, None when the environment
+// does not say.
+fn user_data_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    if cfg!(windows) {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+    }
+}
+
+// Path of `file_name` in the game's own folder under the user data directory.
+pub fn data_file(file_name: &str) -> Option<PathBuf> {
+    user_data_dir().map(|dir| dir.join("asteroidgame").join(file_name))
+}
+
+// Writes to a temporary file next to `path` and renames it over the old one,
+// so a crash halfway leaves the previous contents intact.
+pub fn write_atomically(path: &Path, text: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+    std::fs::write(&temp_path, text)
+        .map_err(|e| format!("Cannot write {}: {}", temp_path.display(), e))?;
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| format!("Cannot replace {}: {}", path.display(), e))
+}
@@ -2,18 +2,25 @@ use sdl2::image::LoadTexture;
 use sdl2::render::{Texture, TextureCreator};
 use sdl2::video::WindowContext;
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+
+use asteroidgame::assets::AssetManifest;
+use asteroidgame::atlas::Atlases;
 
 pub struct TextureManager<'a> {
     loader: &'a TextureCreator<WindowContext>,
     tex_map: HashMap<String, Texture<'a>>,
+    // Atlas and sprite names already reported as missing, so each one is only
+    // printed once until the next reload.
+    missing_sprites: BTreeSet<(String, String)>,
 }
 
 impl<'a> TextureManager<'a> {
     pub fn new(loader: &'a TextureCreator<WindowContext>) -> Self {
         TextureManager {
-            loader: loader,
+            loader,
             tex_map: HashMap::new(),
+            missing_sprites: BTreeSet::new(),
         }
     }
 
@@ -22,13 +29,64 @@ impl<'a> TextureManager<'a> {
         self.tex_map.insert(key.to_string(), texture);
         Ok(())
     }
+
+    // Loads every texture the manifest lists, under its name there.
+    pub fn load_textures(&mut self, manifest: &AssetManifest) -> Result<(), String> {
+        for (name, path) in manifest.textures.iter() {
+            self.load_texture(name, path)?;
+        }
+        Ok(())
+    }
+
+    // Makes sure each atlas's texture is loaded and every sprite lies inside it.
+    pub fn check_atlases(&self, atlases: &Atlases) -> Result<(), String> {
+        for (name, atlas) in atlases.atlases.iter() {
+            let query = self.get_texture(&atlas.texture)?.query();
+            for (sprite_name, sprite) in atlas.sprites.iter() {
+                if sprite.x + sprite.width > query.width || sprite.y + sprite.height > query.height
+                {
+                    return Err(format!(
+                        "Sprite {}/{} lies outside texture {} ({}x{})",
+                        name, sprite_name, atlas.texture, query.width, query.height
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Tells once that a sprite cannot be drawn, it is skipped instead.
+    pub fn report_missing_sprite(&mut self, atlas: &str, sprite: &str) {
+        if self
+            .missing_sprites
+            .insert((atlas.to_string(), sprite.to_string()))
+        {
+            println!(
+                "Cannot draw sprite {:?} from atlas {:?}, skipping it",
+                sprite, atlas
+            );
+        }
+    }
+
+    // Reloaded assets can lose sprites again, which is worth telling about.
+    pub fn forget_missing_sprites(&mut self) {
+        self.missing_sprites.clear();
+    }
+
     pub fn get_texture(&self, key: &String) -> Result<&Texture<'a>, String> {
         match self.tex_map.get(key) {
             None => {
                 let error_msg = format!("Texture {} cannot be found", key);
-                Err(error_msg).into()
+                Err(error_msg)
             }
             Some(texture) => Ok(texture),
         }
     }
+
+    // For changing how a texture is drawn, as with color and alpha mods.
+    pub fn get_texture_mut(&mut self, key: &str) -> Result<&mut Texture<'a>, String> {
+        self.tex_map
+            .get_mut(key)
+            .ok_or_else(|| format!("Texture {} cannot be found", key))
+    }
 }
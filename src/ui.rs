@@ -0,0 +1,44 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+
+pub struct UIElement<'a> {
+    pub texture: Texture<'a>,
+    pub position: Rect,
+}
+
+impl<'a> UIElement<'a> {
+    // Renders `text` once into a texture, creating textures is slow so callers
+    // keep the element around instead of doing this every frame.
+    pub fn text(
+        font: &Font,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        text: &str,
+        color: Color,
+        position: Rect,
+    ) -> Result<Self, String> {
+        let surface = font.render(text).solid(color).map_err(|e| e.to_string())?;
+        let texture = texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())?;
+        Ok(UIElement { texture, position })
+    }
+}
+
+pub fn render_ui(canvas: &mut WindowCanvas, ui_elements: &[UIElement]) -> Result<(), String> {
+    for ui_element in ui_elements {
+        canvas.copy(&ui_element.texture, None, Some(ui_element.position))?;
+    }
+    Ok(())
+}
+
+// Darkens whatever was drawn so far, used behind menus shown on top of the game.
+pub fn dim(canvas: &mut WindowCanvas) -> Result<(), String> {
+    canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+    canvas.fill_rect(None)?;
+    canvas.set_blend_mode(sdl2::render::BlendMode::None);
+    Ok(())
+}
@@ -1,4 +1,6 @@
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Vector2D {
     pub x: f64,
     pub y: f64,
@@ -9,6 +11,20 @@ impl Vector2D {
         Vector2D { x, y }
     }
 
+    // Vector of the given length pointing along a heading in degrees, using the
+    // game's convention of 0 pointing up the screen and angles turning clockwise.
+    pub fn from_heading(degrees: f64, length: f64) -> Self {
+        let radians = degrees.to_radians();
+        Vector2D {
+            x: length * radians.sin(),
+            y: -length * radians.cos(),
+        }
+    }
+
+    pub fn heading(&self) -> f64 {
+        self.x.atan2(-self.y).to_degrees()
+    }
+
     pub fn add(&self, other: Vector2D) -> Vector2D {
         Vector2D {
             x: self.x + other.x,
@@ -33,4 +49,13 @@ impl Vector2D {
     pub fn magnitude(&self) -> f64 {
         (self.x * self.x + self.y * self.y).sqrt()
     }
+    // Same direction with a length of one, a zero vector stays zero.
+    pub fn normalize(&self) -> Vector2D {
+        let magnitude = self.magnitude();
+        if magnitude > 0.0 {
+            self.multiply(1.0 / magnitude)
+        } else {
+            *self
+        }
+    }
 }
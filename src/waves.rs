@@ -0,0 +1,339 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use specs::shrev::EventChannel;
+use specs::{Entities, Join};
+
+use crate::archetypes::{AsteroidArchetypes, AsteroidTier};
+use crate::boundary::PlayField;
+use crate::components;
+use crate::prefab::{self, asteroid_overrides, PrefabRegistry};
+use crate::vector2D::Vector2D;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SizeWeight {
+    pub kind: String,
+    pub size: u32,
+    pub weight: u32,
+}
+
+// What a single wave throws at the player.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveDef {
+    pub count: u32,
+    // Asteroid tiers to pick from by kind and size, each as likely as its
+    // weight, so a wave can mix enemy types.
+    pub sizes: Vec<SizeWeight>,
+    // Scales the speed picked from the tier's range.
+    pub speed_multiplier: f64,
+}
+
+impl WaveDef {
+    pub fn random_size<R: Rng>(&self, rng: &mut R) -> &SizeWeight {
+        let total: u32 = self.sizes.iter().map(|entry| entry.weight).sum();
+        let mut pick = rng.gen_range(0..total);
+        for entry in self.sizes.iter() {
+            if pick < entry.weight {
+                return entry;
+            }
+            pick -= entry.weight;
+        }
+        &self.sizes[self.sizes.len() - 1]
+    }
+}
+
+// How waves past the end of the list keep getting harder, counted from the
+// last wave in it.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct WaveGrowth {
+    pub count_per_wave: f64,
+    pub speed_per_wave: f64,
+    pub max_speed_multiplier: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveConfig {
+    // Seconds between a wave being cleared and the next one arriving.
+    pub countdown: f64,
+    // No asteroid of a new wave spawns closer than this to the player.
+    pub safe_radius: f64,
+    pub waves: Vec<WaveDef>,
+    pub growth: WaveGrowth,
+}
+
+impl WaveConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read waves {}: {}", path, e))?;
+        Self::from_ron(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, String> {
+        let config: WaveConfig = ron::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        // The upper bound depends on the field, see check_field
+        if self.safe_radius < 0.0 {
+            return Err("safe_radius cannot be negative".to_string());
+        }
+        if self.waves.is_empty() {
+            return Err("No waves defined".to_string());
+        }
+        for (index, wave) in self.waves.iter().enumerate() {
+            if wave.sizes.iter().map(|entry| entry.weight).sum::<u32>() == 0 {
+                return Err(format!("Wave {} has no asteroid sizes", index + 1));
+            }
+        }
+        Ok(())
+    }
+
+    // Safe spots are picked within half the field of the player, so the safe
+    // radius has to leave room for them.
+    pub fn check_field(&self, field: &PlayField) -> Result<(), String> {
+        let max_radius = field.width.min(field.height) as f64 / 2.0;
+        if self.safe_radius >= max_radius {
+            return Err(format!("safe_radius must be below {}", max_radius));
+        }
+        Ok(())
+    }
+
+    // Every kind and size a wave can ask for has to be a known asteroid tier.
+    pub fn check_sizes(&self, archetypes: &AsteroidArchetypes) -> Result<(), String> {
+        for (index, wave) in self.waves.iter().enumerate() {
+            if let Some(entry) = wave
+                .sizes
+                .iter()
+                .find(|entry| archetypes.tier(&entry.kind, entry.size).is_none())
+            {
+                return Err(format!(
+                    "Wave {} uses unknown asteroid tier {} {}",
+                    index + 1,
+                    entry.kind,
+                    entry.size
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Composition of wave `number`, counting from 1.
+    pub fn wave(&self, number: u32) -> WaveDef {
+        let index = number.max(1) as usize - 1;
+        if let Some(wave) = self.waves.get(index) {
+            return wave.clone();
+        }
+        let last = &self.waves[self.waves.len() - 1];
+        let past_list = (index + 1 - self.waves.len()) as f64;
+        WaveDef {
+            count: last.count + (past_list * self.growth.count_per_wave).floor() as u32,
+            sizes: last.sizes.clone(),
+            speed_multiplier: (last.speed_multiplier + past_list * self.growth.speed_per_wave)
+                .min(self.growth.max_speed_multiplier),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveEvent {
+    Started { wave: u32 },
+    Cleared { wave: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WaveState {
+    // Waiting `remaining` seconds before the next wave arrives.
+    Countdown { remaining: f64 },
+    // The current wave is in play until its last asteroid is gone.
+    Active,
+}
+
+// Which wave the run is on and what it is doing right now.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WaveDirector {
+    // The wave in play or last cleared, 0 before the first one.
+    pub wave: u32,
+    pub state: WaveState,
+}
+
+impl WaveDirector {
+    pub fn new(countdown: f64) -> Self {
+        WaveDirector {
+            wave: 0,
+            state: WaveState::Countdown {
+                remaining: countdown,
+            },
+        }
+    }
+
+    // Seconds until the next wave, when one is on its way.
+    pub fn countdown(&self) -> Option<f64> {
+        match self.state {
+            WaveState::Countdown { remaining } => Some(remaining),
+            WaveState::Active => None,
+        }
+    }
+}
+
+// A point whose distance from `avoid` is between `safe_radius` and half the
+// field. The world wraps, so every such point is on the field once wrapped and
+// no retrying is needed.
+pub fn safe_spot<R: Rng>(
+    field: &PlayField,
+    avoid: Vector2D,
+    safe_radius: f64,
+    rng: &mut R,
+) -> Vector2D {
+    let max_radius = field.width.min(field.height) as f64 / 2.0;
+    // Picking the squared distance keeps the spots evenly spread over the ring.
+    // A safe radius that leaves no ring puts them on the edge of the field.
+    let distance = if safe_radius < max_radius {
+        rng.gen_range(safe_radius * safe_radius..max_radius * max_radius)
+            .sqrt()
+    } else {
+        max_radius
+    };
+    let spot = avoid.add(Vector2D::from_heading(rng.gen_range(0.0..360.0), distance));
+    field.wrap(spot)
+}
+
+// What spawning asteroids needs, borrowed from a system's data or the world.
+pub struct AsteroidSpawner<'s, 'a> {
+    pub archetypes: &'s AsteroidArchetypes,
+    pub prefabs: &'s PrefabRegistry,
+    pub lazy: &'s LazyUpdate,
+    pub entities: &'s Entities<'a>,
+    pub field: &'s PlayField,
+}
+
+impl<'s, 'a> AsteroidSpawner<'s, 'a> {
+    // Spawns `count` asteroids out of the way of `avoid`, tiers are chosen by
+    // `pick_tier`.
+    pub fn spawn<'t, R: Rng>(
+        &self,
+        count: u32,
+        mut pick_tier: impl FnMut(&mut R) -> Option<&'t AsteroidTier>,
+        speed_multiplier: f64,
+        avoid: Vector2D,
+        safe_radius: f64,
+        rng: &mut R,
+    ) {
+        for _ in 0..count {
+            let tier = match pick_tier(rng) {
+                Some(tier) => tier,
+                None => continue,
+            };
+            let position = safe_spot(self.field, avoid, safe_radius, rng);
+            let heading = rng.gen_range(0.0..360.0);
+            let mut overrides = asteroid_overrides(tier, position, heading, rng);
+            if let Some(velocity) = overrides.velocity.as_mut() {
+                velocity.vel = velocity.vel.multiply(speed_multiplier);
+            }
+            self.prefabs.spawn_lazy(
+                &prefab::asteroid_name(&tier.kind, tier.size),
+                self.lazy,
+                self.entities,
+                overrides,
+            );
+        }
+    }
+}
+
+// Runs the waves: once every asteroid is gone it counts down, then sends the
+// next wave in and moves the level along with it.
+pub struct WaveDirectorSystem;
+
+impl<'a> System<'a> for WaveDirectorSystem {
+    type SystemData = (
+        WriteExpect<'a, WaveDirector>,
+        ReadExpect<'a, WaveConfig>,
+        ReadExpect<'a, AsteroidArchetypes>,
+        ReadExpect<'a, PrefabRegistry>,
+        Write<'a, EventChannel<WaveEvent>>,
+        WriteStorage<'a, components::GameData>,
+        ReadStorage<'a, components::Asteroid>,
+        ReadStorage<'a, components::Player>,
+        ReadStorage<'a, components::Position>,
+        ReadExpect<'a, PlayField>,
+        Read<'a, components::DeltaTime>,
+        WriteExpect<'a, components::GameRng>,
+        Read<'a, LazyUpdate>,
+        Entities<'a>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut director,
+            config,
+            archetypes,
+            prefabs,
+            mut events,
+            mut gamedatas,
+            asteroids,
+            players,
+            positions,
+            field,
+            delta_time,
+            mut rng,
+            lazy,
+            entities,
+        ) = data;
+
+        match director.state {
+            WaveState::Active => {
+                if asteroids.is_empty() {
+                    events.single_write(WaveEvent::Cleared {
+                        wave: director.wave,
+                    });
+                    director.state = WaveState::Countdown {
+                        remaining: config.countdown,
+                    };
+                }
+            }
+            WaveState::Countdown { remaining } => {
+                let remaining = remaining - delta_time.0;
+                if remaining > 0.0 {
+                    director.state = WaveState::Countdown { remaining };
+                    return;
+                }
+
+                director.wave += 1;
+                director.state = WaveState::Active;
+                let wave = config.wave(director.wave);
+                // Without a ship in play keep the center clear, that is where
+                // the next one shows up
+                let avoid = (&players, &positions)
+                    .join()
+                    .map(|(_, position)| position.pos)
+                    .next()
+                    .unwrap_or(field.center());
+                let spawner = AsteroidSpawner {
+                    archetypes: &archetypes,
+                    prefabs: &prefabs,
+                    lazy: &lazy,
+                    entities: &entities,
+                    field: &field,
+                };
+                spawner.spawn(
+                    wave.count,
+                    |rng| {
+                        let entry = wave.random_size(rng);
+                        archetypes.tier(&entry.kind, entry.size)
+                    },
+                    wave.speed_multiplier,
+                    avoid,
+                    config.safe_radius,
+                    &mut *rng,
+                );
+                for gamedata in (&mut gamedatas).join() {
+                    gamedata.level = director.wave;
+                }
+                events.single_write(WaveEvent::Started {
+                    wave: director.wave,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use asteroidgame::animation::{AnimationFinished, Animator};
+use asteroidgame::atlas::Atlases;
+use asteroidgame::components::{Animation, DeltaTime, RenderLayer, Renderable};
+use specs::shrev::EventChannel;
+use specs::{Builder, Entity, RunNow, World, WorldExt};
+
+const ATLASES: &str = r#"(
+    atlases: {
+        "ship": (
+            texture: "ship",
+            sprites: {
+                "a": (x: 0, y: 0, width: 10, height: 10),
+                "b": (x: 10, y: 0, width: 10, height: 10),
+                "c": (x: 20, y: 0, width: 10, height: 10),
+            },
+            clips: {
+                "idle": (mode: PingPong, frames: [
+                    (sprite: "a", duration: 1.0),
+                    (sprite: "b", duration: 1.0),
+                    (sprite: "c", duration: 1.0),
+                ]),
+                "thrust": (mode: Loop, frames: [(sprite: "a", duration: 1.0)]),
+                "death": (mode: Once, frames: [
+                    (sprite: "b", duration: 1.0),
+                    (sprite: "c", duration: 1.0),
+                ]),
+            },
+        ),
+        "rocket": (
+            texture: "rocket",
+            sprites: {"rocket": (x: 0, y: 0, width: 5, height: 20)},
+        ),
+    },
+)"#;
+
+fn world_playing(clip: &str) -> (World, Entity) {
+    let mut world = World::new();
+    world.register::<Animation>();
+    world.register::<Renderable>();
+    world.insert(Atlases::from_ron(ATLASES).expect("Test atlases should load"));
+    world.insert(DeltaTime(1.0));
+    world.insert(EventChannel::<AnimationFinished>::new());
+    let entity = world
+        .create_entity()
+        .with(Animation::new("ship", clip))
+        .with(Renderable {
+            atlas: String::from("ship"),
+            sprite: String::new(),
+            output_width: 10,
+            output_height: 10,
+            render_rotation: 0.0,
+            layer: RenderLayer::Player,
+        })
+        .build();
+    (world, entity)
+}
+
+fn sprite_shown(world: &World, entity: Entity) -> String {
+    world
+        .read_storage::<Renderable>()
+        .get(entity)
+        .unwrap()
+        .sprite
+        .clone()
+}
+
+#[test]
+fn ping_pong_runs_back_and_forth() {
+    let (world, entity) = world_playing("idle");
+    let mut shown = Vec::new();
+    for _ in 0..6 {
+        Animator.run_now(&world);
+        shown.push(sprite_shown(&world, entity));
+    }
+    assert_eq!(shown, ["b", "c", "b", "a", "b", "c"]);
+}
+
+#[test]
+fn once_stops_on_its_last_frame_and_reports_it() {
+    let (world, entity) = world_playing("death");
+    let mut reader = world
+        .write_resource::<EventChannel<AnimationFinished>>()
+        .register_reader();
+    for _ in 0..5 {
+        Animator.run_now(&world);
+    }
+    assert_eq!(sprite_shown(&world, entity), "c");
+
+    let channel = world.read_resource::<EventChannel<AnimationFinished>>();
+    let finished: Vec<_> = channel.read(&mut reader).collect();
+    assert_eq!(finished.len(), 1);
+    assert_eq!(finished[0].entity, entity);
+    assert_eq!(finished[0].clip, "death");
+}
+
+#[test]
+fn clips_cannot_show_sprites_the_atlas_lacks() {
+    let broken = ATLASES.replace(
+        r#"(sprite: "c", duration: 1.0),
+                ]),
+            },"#,
+        r#"(sprite: "d", duration: 1.0),
+                ]),
+            },"#,
+    );
+    assert_ne!(broken, ATLASES);
+    assert!(Atlases::from_ron(&broken)
+        .unwrap_err()
+        .contains("unknown sprite"));
+}
@@ -0,0 +1,75 @@
+use asteroidgame::assets::{AssetKind, AssetManifest, AssetWatcher, ASSET_MANIFEST_PATH};
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+// A manifest whose every entry points at `path`.
+fn manifest_pointing_at(path: &str) -> AssetManifest {
+    let mut manifest =
+        AssetManifest::load(ASSET_MANIFEST_PATH).expect("Asset manifest should load");
+    for file in manifest
+        .textures
+        .values_mut()
+        .chain(manifest.data.values_mut())
+    {
+        *file = path.to_string();
+    }
+    for font in manifest.fonts.values_mut() {
+        font.path = path.to_string();
+    }
+    manifest
+}
+
+fn scratch_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("asteroidgame-{}-{}", std::process::id(), name));
+    std::fs::write(&path, "first").unwrap();
+    path
+}
+
+#[test]
+fn every_missing_file_is_reported() {
+    let mut manifest = AssetManifest::load(ASSET_MANIFEST_PATH).unwrap();
+    manifest
+        .textures
+        .insert("ship".to_string(), "assets/nothing.png".to_string());
+    manifest
+        .data
+        .insert("waves".to_string(), "assets/data/nothing.ron".to_string());
+
+    let error = manifest.check_files().unwrap_err();
+    assert!(error.contains(r#"texture "ship" at assets/nothing.png"#));
+    assert!(error.contains(r#"data file "waves" at assets/data/nothing.ron"#));
+}
+
+#[test]
+fn manifest_has_to_list_every_data_file() {
+    let text = std::fs::read_to_string(ASSET_MANIFEST_PATH).unwrap();
+    let without_waves: String = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("\"waves\""))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(AssetManifest::from_ron(&without_waves)
+        .unwrap_err()
+        .contains("waves"));
+}
+
+#[test]
+fn watcher_reports_saved_files() {
+    let path = scratch_file("watched.ron");
+    let manifest = manifest_pointing_at(path.to_str().unwrap());
+    let mut watcher = AssetWatcher::new(&manifest, Duration::ZERO);
+    assert!(watcher.changed().is_empty());
+
+    // Some file systems only keep modification times to the second
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    let later = std::fs::metadata(&path).unwrap().modified().unwrap() + Duration::from_secs(2);
+    file.set_modified(later).unwrap();
+
+    let changed = watcher.changed();
+    let files = manifest.textures.len() + manifest.fonts.len() + manifest.data.len();
+    assert_eq!(changed.len(), files);
+    assert!(changed.contains(&(AssetKind::Data, "waves".to_string())));
+    assert!(watcher.changed().is_empty());
+    std::fs::remove_file(&path).ok();
+}
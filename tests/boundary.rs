@@ -0,0 +1,203 @@
+use asteroidgame::archetypes::ROCK;
+use asteroidgame::boundary::{BoundaryEnforcer, PlayField};
+use asteroidgame::broadphase::{BroadphaseBuilder, SpatialGrid};
+use asteroidgame::collision::{CollisionDetector, CollisionEvent};
+use asteroidgame::components::{Asteroid, Boundary, Collider, Player, Position, Rocket, Velocity};
+use asteroidgame::vector2D::Vector2D;
+use specs::shrev::EventChannel;
+use specs::{Builder, Entity, RunNow, World, WorldExt};
+
+const FIELD: PlayField = PlayField {
+    width: 800,
+    height: 600,
+};
+
+fn world() -> World {
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Velocity>();
+    world.register::<Boundary>();
+    world.register::<Collider>();
+    world.register::<Player>();
+    world.register::<Asteroid>();
+    world.register::<Rocket>();
+    world.insert(FIELD);
+    world.insert(SpatialGrid::for_field(&FIELD));
+    world.insert(EventChannel::<CollisionEvent>::new());
+    world
+}
+
+fn spawn(world: &mut World, boundary: Boundary, pos: Vector2D, vel: Vector2D) -> Entity {
+    world
+        .create_entity()
+        .with(Position { pos, rot: 0.0 })
+        .with(Velocity { vel })
+        .with(Collider { radius: 10.0 })
+        .with(boundary)
+        .build()
+}
+
+fn enforce(world: &mut World) {
+    BoundaryEnforcer.run_now(world);
+    world.maintain();
+}
+
+fn position(world: &World, entity: Entity) -> Vector2D {
+    world.read_storage::<Position>().get(entity).unwrap().pos
+}
+
+fn velocity(world: &World, entity: Entity) -> Vector2D {
+    world.read_storage::<Velocity>().get(entity).unwrap().vel
+}
+
+#[test]
+fn wrapping_entity_comes_back_on_the_opposite_edge() {
+    let mut world = world();
+    let still = Vector2D::default();
+    let past_right = spawn(
+        &mut world,
+        Boundary::Wrap,
+        Vector2D::new(805.0, 300.0),
+        still,
+    );
+    let past_left = spawn(
+        &mut world,
+        Boundary::Wrap,
+        Vector2D::new(-5.0, 300.0),
+        still,
+    );
+    let past_bottom = spawn(
+        &mut world,
+        Boundary::Wrap,
+        Vector2D::new(400.0, 610.0),
+        still,
+    );
+    let past_top = spawn(
+        &mut world,
+        Boundary::Wrap,
+        Vector2D::new(400.0, -10.0),
+        still,
+    );
+    enforce(&mut world);
+
+    assert_eq!(position(&world, past_right), Vector2D::new(5.0, 300.0));
+    assert_eq!(position(&world, past_left), Vector2D::new(795.0, 300.0));
+    assert_eq!(position(&world, past_bottom), Vector2D::new(400.0, 10.0));
+    assert_eq!(position(&world, past_top), Vector2D::new(400.0, 590.0));
+}
+
+#[test]
+fn bouncing_entity_is_kept_on_the_field_and_turned_around() {
+    let mut world = world();
+    let right = spawn(
+        &mut world,
+        Boundary::Bounce,
+        Vector2D::new(795.0, 300.0),
+        Vector2D::new(50.0, 20.0),
+    );
+    let top_left = spawn(
+        &mut world,
+        Boundary::Bounce,
+        Vector2D::new(-4.0, 3.0),
+        Vector2D::new(-30.0, -40.0),
+    );
+    enforce(&mut world);
+
+    // Clamped by the collider radius, only the part of the velocity going
+    // through the edge flips
+    assert_eq!(position(&world, right), Vector2D::new(790.0, 300.0));
+    assert_eq!(velocity(&world, right), Vector2D::new(-50.0, 20.0));
+    assert_eq!(position(&world, top_left), Vector2D::new(10.0, 10.0));
+    assert_eq!(velocity(&world, top_left), Vector2D::new(30.0, 40.0));
+}
+
+#[test]
+fn bouncing_entity_already_heading_back_is_not_turned_again() {
+    let mut world = world();
+    let entity = spawn(
+        &mut world,
+        Boundary::Bounce,
+        Vector2D::new(798.0, 300.0),
+        Vector2D::new(-50.0, 0.0),
+    );
+    enforce(&mut world);
+
+    assert_eq!(position(&world, entity), Vector2D::new(790.0, 300.0));
+    assert_eq!(velocity(&world, entity), Vector2D::new(-50.0, 0.0));
+}
+
+#[test]
+fn despawning_entity_is_deleted_once_fully_off_the_field() {
+    let mut world = world();
+    let still = Vector2D::default();
+    let gone = spawn(
+        &mut world,
+        Boundary::Despawn,
+        Vector2D::new(811.0, 300.0),
+        still,
+    );
+    let overhanging = spawn(
+        &mut world,
+        Boundary::Despawn,
+        Vector2D::new(805.0, 300.0),
+        still,
+    );
+    let above = spawn(
+        &mut world,
+        Boundary::Despawn,
+        Vector2D::new(400.0, -11.0),
+        still,
+    );
+    enforce(&mut world);
+
+    assert!(!world.is_alive(gone));
+    assert!(!world.is_alive(above));
+    assert!(world.is_alive(overhanging));
+}
+
+#[test]
+fn colliders_straddling_the_wrap_seam_hit_each_other() {
+    let mut world = world();
+    let mut reader = world
+        .write_resource::<EventChannel<CollisionEvent>>()
+        .register_reader();
+    let still = Vector2D::default();
+    let player = spawn(
+        &mut world,
+        Boundary::Wrap,
+        Vector2D::new(795.0, 300.0),
+        still,
+    );
+    let asteroid = spawn(&mut world, Boundary::Wrap, Vector2D::new(3.0, 300.0), still);
+    world
+        .write_storage::<Player>()
+        .insert(
+            player,
+            Player {
+                can_take_damage: true,
+            },
+        )
+        .unwrap();
+    world
+        .write_storage::<Asteroid>()
+        .insert(
+            asteroid,
+            Asteroid {
+                rotation_speed: 0.0,
+                kind: ROCK.to_string(),
+                size: 1,
+            },
+        )
+        .unwrap();
+
+    BroadphaseBuilder.run_now(&world);
+    CollisionDetector.run_now(&world);
+
+    let events = world.read_resource::<EventChannel<CollisionEvent>>();
+    let hits: Vec<_> = events.read(&mut reader).copied().collect();
+    assert_eq!(hits.len(), 1);
+    assert!(matches!(
+        hits[0],
+        CollisionEvent::PlayerHitAsteroid { player: p, asteroid: a, .. } if p == player && a == asteroid
+    ));
+}
@@ -0,0 +1,94 @@
+use asteroidgame::archetypes::{AsteroidArchetypes, ROCK};
+use asteroidgame::assets::{self, AssetManifest, ASSET_MANIFEST_PATH};
+use asteroidgame::boundary::PlayField;
+use asteroidgame::broadphase::SpatialGrid;
+use asteroidgame::vector2D::Vector2D;
+use specs::{Builder, Entity, World, WorldExt};
+
+fn entities(count: usize) -> Vec<Entity> {
+    let mut world = World::new();
+    (0..count).map(|_| world.create_entity().build()).collect()
+}
+
+fn biggest_asteroid_radius() -> f64 {
+    let manifest = AssetManifest::load(ASSET_MANIFEST_PATH).expect("Asset manifest should load");
+    let archetypes = AsteroidArchetypes::load(manifest.data(assets::ASTEROIDS).unwrap())
+        .expect("Asteroid archetypes should load");
+    archetypes
+        .tier(ROCK, 5)
+        .expect("Size 5 asteroids exist")
+        .hitbox_radius
+}
+
+fn field_grid() -> SpatialGrid {
+    SpatialGrid::for_field(&PlayField {
+        width: 800,
+        height: 600,
+    })
+}
+
+fn found_near(grid: &SpatialGrid, point: Vector2D) -> Vec<Entity> {
+    let mut found = Vec::new();
+    grid.query(point, 1.0, &mut found);
+    found
+}
+
+#[test]
+fn big_asteroid_is_found_from_every_cell_it_covers() {
+    let radius = biggest_asteroid_radius();
+    assert!(radius > 50.0, "a size 5 asteroid should span several cells");
+    let asteroid = entities(1)[0];
+    let mut grid = field_grid();
+    // On the corner of four cells, reaching into the ones around them
+    let center = Vector2D::new(400.0, 300.0);
+    grid.insert(asteroid, center, radius);
+
+    for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+        let near_edge = center.add(Vector2D::new(x * (radius - 2.0), y * (radius - 2.0)));
+        assert_eq!(found_near(&grid, near_edge), vec![asteroid]);
+    }
+    // Two cells away is out of reach
+    assert!(found_near(&grid, Vector2D::new(150.0, 50.0)).is_empty());
+}
+
+#[test]
+fn bodies_outside_the_field_go_into_the_border_cells() {
+    let bodies = entities(4);
+    let mut grid = field_grid();
+    grid.insert(bodies[0], Vector2D::new(-250.0, -90.0), 10.0);
+    grid.insert(bodies[1], Vector2D::new(1200.0, 700.0), 10.0);
+    grid.insert(bodies[2], Vector2D::new(800.0, 600.0), 0.0);
+    grid.insert(bodies[3], Vector2D::new(0.0, 0.0), 0.0);
+
+    let mut top_left = found_near(&grid, Vector2D::new(10.0, 10.0));
+    top_left.sort();
+    assert_eq!(top_left, vec![bodies[0], bodies[3]]);
+    let mut bottom_right = found_near(&grid, Vector2D::new(790.0, 590.0));
+    bottom_right.sort();
+    assert_eq!(bottom_right, vec![bodies[1], bodies[2]]);
+    // Queries from outside are clamped the same way
+    assert_eq!(
+        found_near(&grid, Vector2D::new(5000.0, 5000.0)),
+        bottom_right
+    );
+}
+
+#[test]
+fn query_lists_each_candidate_once() {
+    let radius = biggest_asteroid_radius();
+    let bodies = entities(2);
+    let (asteroid, ship) = (bodies[0], bodies[1]);
+    let mut grid = field_grid();
+    grid.insert(asteroid, Vector2D::new(400.0, 300.0), radius);
+    grid.insert(ship, Vector2D::new(390.0, 310.0), 16.0);
+
+    // The query covers every cell both of them are in
+    let mut found = Vec::new();
+    grid.query(Vector2D::new(400.0, 300.0), radius, &mut found);
+    found.sort();
+    assert_eq!(found, vec![asteroid, ship]);
+
+    // A second query starts over rather than adding to the last one
+    grid.query(Vector2D::new(700.0, 500.0), 1.0, &mut found);
+    assert!(found.is_empty());
+}
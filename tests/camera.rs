@@ -0,0 +1,47 @@
+use asteroidgame::assets::{self, AssetManifest, ASSET_MANIFEST_PATH};
+use asteroidgame::boundary::PlayField;
+use asteroidgame::camera::{Camera, CameraConfig, CameraMode};
+use asteroidgame::vector2D::Vector2D;
+
+fn camera_for(mode: CameraMode, field: &PlayField) -> Camera {
+    let manifest = AssetManifest::load(ASSET_MANIFEST_PATH).expect("Asset manifest should load");
+    let mut config = CameraConfig::load(manifest.data(assets::CAMERA).unwrap())
+        .expect("Camera config should load");
+    config.view_width = 800;
+    config.view_height = 600;
+    config.mode = mode;
+    config.zoom = 2.0;
+    Camera::new(&config, field)
+}
+
+#[test]
+fn screen_and_world_coordinates_convert_back_and_forth() {
+    let field = PlayField {
+        width: 3000,
+        height: 2000,
+    };
+    let mut camera = camera_for(CameraMode::Follow, &field);
+    camera.follow(&field, Some(Vector2D::new(1200.0, 300.0)));
+
+    let world = Vector2D::new(1250.0, 280.0);
+    let screen = camera.world_to_screen(camera.position, world);
+    assert_eq!(screen, Vector2D::new(500.0, 260.0));
+    assert_eq!(camera.screen_to_world(screen), world);
+}
+
+#[test]
+fn field_stays_centered_along_axes_that_fit() {
+    let field = PlayField {
+        width: 3000,
+        height: 200,
+    };
+    let mut camera = camera_for(CameraMode::Follow, &field);
+    camera.follow(&field, Some(Vector2D::new(1200.0, 30.0)));
+    assert_eq!(camera.position, Vector2D::new(1200.0, 100.0));
+    assert!(!camera.shows_whole_field(&field));
+
+    let mut camera = camera_for(CameraMode::Fit, &field);
+    camera.follow(&field, Some(Vector2D::new(1200.0, 30.0)));
+    assert_eq!(camera.position, field.center());
+    assert!(camera.shows_whole_field(&field));
+}
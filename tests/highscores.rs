@@ -0,0 +1,142 @@
+use asteroidgame::highscores::{
+    civil_from_days, today, HighScoreEntry, HighScoreTable, HIGH_SCORES_VERSION, MAX_HIGH_SCORES,
+};
+
+use std::path::PathBuf;
+
+fn entry(initials: &str, score: u32) -> HighScoreEntry {
+    HighScoreEntry {
+        initials: initials.to_string(),
+        score,
+        level: 1,
+        date: "2024-02-29".to_string(),
+        mode: "directional".to_string(),
+    }
+}
+
+fn table(scores: &[u32]) -> HighScoreTable {
+    let mut table = HighScoreTable::default();
+    for &score in scores {
+        table.insert(entry("AAA", score)).unwrap();
+    }
+    table
+}
+
+fn scores(table: &HighScoreTable) -> Vec<u32> {
+    table.entries.iter().map(|entry| entry.score).collect()
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("asteroidgame-{}-{}", std::process::id(), name));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn any_score_qualifies_for_a_table_with_room() {
+    let table = table(&[500, 400]);
+    assert!(table.qualifies(1));
+    assert!(table.qualifies(1000));
+    assert!(!table.qualifies(0));
+}
+
+#[test]
+fn full_table_only_takes_scores_above_the_lowest() {
+    let table = table(&[100, 90, 80, 70, 60, 50, 40, 30, 20, 10]);
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+    assert!(table.qualifies(11));
+    assert!(!table.qualifies(10));
+    assert!(!table.qualifies(5));
+}
+
+#[test]
+fn insert_keeps_the_table_sorted_and_returns_the_rank() {
+    let mut table = table(&[300, 100]);
+    assert_eq!(table.insert(entry("BBB", 200)), Some(1));
+    assert_eq!(table.insert(entry("CCC", 400)), Some(0));
+    assert_eq!(table.insert(entry("DDD", 50)), Some(4));
+    assert_eq!(scores(&table), [400, 300, 200, 100, 50]);
+}
+
+#[test]
+fn ties_go_below_the_scores_already_there() {
+    let mut table = table(&[300, 200, 100]);
+    assert_eq!(table.insert(entry("NEW", 200)), Some(2));
+    assert_eq!(table.entries[1].initials, "AAA");
+    assert_eq!(table.entries[2].initials, "NEW");
+}
+
+#[test]
+fn insert_into_a_full_table_drops_the_lowest() {
+    let mut table = table(&[100, 90, 80, 70, 60, 50, 40, 30, 20, 10]);
+    assert_eq!(table.insert(entry("NEW", 55)), Some(5));
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+    assert_eq!(scores(&table), [100, 90, 80, 70, 60, 55, 50, 40, 30, 20]);
+
+    assert_eq!(table.insert(entry("LOW", 20)), None);
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+    assert_eq!(*scores(&table).last().unwrap(), 20);
+}
+
+#[test]
+fn saved_table_loads_back() {
+    let path = scratch_dir("saved-scores").join("highscores.ron");
+    let table = table(&[300, 200, 100]);
+    table.save(&path).unwrap();
+    assert_eq!(HighScoreTable::load(&path).entries, table.entries);
+    // Written next to the file first, then moved over it
+    assert!(!path.with_extension("ron.tmp").exists());
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+}
+
+#[test]
+fn corrupt_file_is_backed_up_and_replaced_by_an_empty_table() {
+    let dir = scratch_dir("corrupt-scores");
+    let path = dir.join("highscores.ron");
+    std::fs::write(&path, "(version: 1, entries: [(").unwrap();
+
+    assert!(HighScoreTable::load(&path).entries.is_empty());
+    assert!(!path.exists());
+    let backup = dir.join("highscores.ron.corrupt");
+    assert_eq!(
+        std::fs::read_to_string(&backup).unwrap(),
+        "(version: 1, entries: [("
+    );
+
+    // The next save starts the file over and leaves the backup alone
+    table(&[100]).save(&path).unwrap();
+    assert_eq!(scores(&HighScoreTable::load(&path)), [100]);
+    assert!(backup.exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn unknown_version_is_rejected() {
+    let text = format!("(version: {}, entries: [])", HIGH_SCORES_VERSION + 1);
+    let error = HighScoreTable::from_ron(&text).unwrap_err();
+    assert!(error.contains("unsupported version"));
+}
+
+#[test]
+fn days_since_epoch_become_civil_dates() {
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    assert_eq!(civil_from_days(59), (1970, 3, 1));
+    assert_eq!(civil_from_days(10956), (1999, 12, 31));
+    assert_eq!(civil_from_days(10957), (2000, 1, 1));
+    assert_eq!(civil_from_days(11016), (2000, 2, 29));
+    assert_eq!(civil_from_days(19782), (2024, 2, 29));
+    // 2100 is not a leap year
+    assert_eq!(civil_from_days(47540), (2100, 2, 28));
+    assert_eq!(civil_from_days(47541), (2100, 3, 1));
+}
+
+#[test]
+fn today_is_formatted_as_a_date() {
+    let date = today();
+    assert_eq!(date.len(), 10);
+    assert_eq!(date.as_bytes()[4], b'-');
+    assert_eq!(date.as_bytes()[7], b'-');
+    assert!(date.as_str() >= "2024-01-01");
+}
@@ -0,0 +1,95 @@
+use asteroidgame::input::{
+    Action, Aim, GamepadSettings, InputCollector, InputEvent, InputState, PadAxis,
+};
+use asteroidgame::vector2D::Vector2D;
+
+const PAD: u32 = 0;
+const OTHER_PAD: u32 = 1;
+
+fn collector() -> InputCollector {
+    InputCollector::new(GamepadSettings::default())
+}
+
+#[test]
+fn left_stick_inside_dead_zone_does_not_move() {
+    let mut input = collector();
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::LeftX, 0.2));
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::LeftY, -0.1));
+    assert!(input.snapshot().actions.is_empty());
+
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::LeftX, 0.8));
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::LeftY, -0.9));
+    let actions = input.snapshot().actions;
+    assert!(actions.contains(Action::MoveRight));
+    assert!(actions.contains(Action::MoveUp));
+    assert!(!actions.contains(Action::MoveLeft));
+}
+
+#[test]
+fn right_stick_aims_and_keeps_direction_when_released() {
+    let mut input = collector();
+    input.handle(InputEvent::AimAt(Vector2D::new(10.0, 10.0)));
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::RightX, 1.0));
+    assert_eq!(input.snapshot().aim, Aim::Towards(Vector2D::new(1.0, 0.0)));
+
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::RightX, 0.1));
+    assert_eq!(input.snapshot().aim, Aim::Towards(Vector2D::new(1.0, 0.0)));
+}
+
+#[test]
+fn trigger_tap_between_ticks_is_pressed_once() {
+    let mut input = collector();
+    let mut state = InputState::default();
+
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::RightTrigger, 1.0));
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::RightTrigger, 0.0));
+    state.update(&input.snapshot());
+    assert!(state.was_pressed(Action::Fire));
+
+    state.update(&input.snapshot());
+    assert!(!state.is_held(Action::Fire));
+    assert!(state.was_released(Action::Fire));
+}
+
+#[test]
+fn disconnect_releases_pad_actions() {
+    let mut input = collector();
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::LeftX, -1.0));
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::RightTrigger, 1.0));
+    input.snapshot();
+
+    input.handle(InputEvent::PadDown(PAD, Action::SpawnWave));
+    input.snapshot();
+
+    input.handle(InputEvent::PadDisconnected(PAD));
+    assert!(input.snapshot().actions.is_empty());
+}
+
+#[test]
+fn disconnect_leaves_other_pads_alone() {
+    let mut input = collector();
+    input.handle(InputEvent::PadAxis(PAD, PadAxis::LeftX, -1.0));
+    input.handle(InputEvent::PadDown(PAD, Action::Fire));
+    input.handle(InputEvent::PadAxis(OTHER_PAD, PadAxis::LeftY, 1.0));
+    input.handle(InputEvent::PadDown(OTHER_PAD, Action::SpawnWave));
+    input.snapshot();
+
+    input.handle(InputEvent::PadDisconnected(OTHER_PAD));
+    let actions = input.snapshot().actions;
+    assert!(actions.contains(Action::MoveLeft));
+    assert!(actions.contains(Action::Fire));
+    assert!(!actions.contains(Action::MoveDown));
+    assert!(!actions.contains(Action::SpawnWave));
+}
+
+#[test]
+fn button_held_by_two_sources_stays_held_until_both_release() {
+    let mut input = collector();
+    input.handle(InputEvent::Down(Action::Fire));
+    input.handle(InputEvent::Down(Action::Fire));
+    input.handle(InputEvent::Up(Action::Fire));
+    assert!(input.snapshot().actions.contains(Action::Fire));
+
+    input.handle(InputEvent::Up(Action::Fire));
+    assert!(!input.snapshot().actions.contains(Action::Fire));
+}
@@ -0,0 +1,36 @@
+use asteroidgame::lives::LivesConfig;
+
+fn lives_ron(safe_radius: &str, extra_life_every: &str) -> String {
+    format!(
+        "(
+            starting_lives: 3,
+            respawn_delay: 2.0,
+            invulnerability: 3.0,
+            safe_radius: {},
+            extra_life_scores: [10, 25],
+            extra_life_every: {},
+        )",
+        safe_radius, extra_life_every
+    )
+}
+
+#[test]
+fn safe_radius_has_to_be_a_distance() {
+    assert!(LivesConfig::from_ron(&lives_ron("120.0", "None")).is_ok());
+    assert!(LivesConfig::from_ron(&lives_ron("0.0", "None")).is_ok());
+    for safe_radius in ["-1.0", "NaN", "inf"] {
+        let error = LivesConfig::from_ron(&lives_ron(safe_radius, "None")).unwrap_err();
+        assert!(error.contains("safe_radius"), "{}: {}", safe_radius, error);
+    }
+}
+
+#[test]
+fn extra_lives_stop_before_the_score_overflows() {
+    let config = LivesConfig::from_ron(&lives_ron("120.0", "Some(1000000000)")).unwrap();
+    assert_eq!(config.extra_life_score(0), Some(10));
+    assert_eq!(config.extra_life_score(1), Some(25));
+    assert_eq!(config.extra_life_score(2), Some(1_000_000_025));
+    assert_eq!(config.extra_life_score(5), Some(4_000_000_025));
+    assert_eq!(config.extra_life_score(6), None);
+    assert_eq!(config.extra_life_score(u32::MAX), None);
+}
@@ -0,0 +1,53 @@
+use asteroidgame::assets::{self, AssetManifest, ASSET_MANIFEST_PATH};
+use asteroidgame::particles::{ParticleConfig, Particles, SHIP_EXPLOSION};
+use asteroidgame::vector2D::Vector2D;
+
+fn manifest() -> AssetManifest {
+    AssetManifest::load(ASSET_MANIFEST_PATH).expect("Asset manifest should load")
+}
+
+fn config() -> ParticleConfig {
+    let manifest = manifest();
+    ParticleConfig::load(manifest.data(assets::PARTICLES).unwrap())
+        .expect("Particle config should load")
+}
+
+#[test]
+fn pool_never_grows_past_max_particles() {
+    let mut config = config();
+    config.max_particles = 100;
+    let mut particles = Particles::new(&config, 1);
+    for _ in 0..10 {
+        particles.burst(SHIP_EXPLOSION, Vector2D::new(0.0, 0.0), 0.0, 1.0);
+    }
+    assert_eq!(particles.len(), 100);
+}
+
+#[test]
+fn particles_are_gone_after_their_lifetime() {
+    let config = config();
+    let longest = config.emitters[SHIP_EXPLOSION].lifetime.max;
+    let mut particles = Particles::new(&config, 1);
+    particles.burst(SHIP_EXPLOSION, Vector2D::new(0.0, 0.0), 0.0, 1.0);
+    assert!(!particles.is_empty());
+
+    let mut time = 0.0;
+    while time <= longest {
+        particles.update(1.0 / 60.0);
+        time += 1.0 / 60.0;
+    }
+    assert!(particles.is_empty());
+}
+
+#[test]
+fn emitters_only_tint_listed_textures() {
+    let manifest = manifest();
+    let mut config = config();
+    assert!(config.check_textures(&manifest).is_ok());
+
+    config.emitters.get_mut(SHIP_EXPLOSION).unwrap().texture = Some("smoke".to_string());
+    assert!(config
+        .check_textures(&manifest)
+        .unwrap_err()
+        .contains("\"smoke\""));
+}
@@ -0,0 +1,68 @@
+use asteroidgame::assets::{self, AssetManifest, ASSET_MANIFEST_PATH};
+use asteroidgame::atlas::Atlases;
+use asteroidgame::components::{Animation, Renderable};
+use asteroidgame::input::Action;
+use asteroidgame::simulation::{InputSnapshot, Simulation, SimulationConfig};
+use specs::{Join, WorldExt};
+
+fn config() -> SimulationConfig {
+    SimulationConfig::load().expect("Data files should load")
+}
+
+// Fires now and then, so rockets are in flight too.
+fn input_for(tick: u32) -> InputSnapshot {
+    let mut input = InputSnapshot::default();
+    if tick.is_multiple_of(20) {
+        input.actions.insert(Action::Fire);
+    }
+    input
+}
+
+#[test]
+fn renamed_sprites_and_atlases_are_resolved_after_a_reload() {
+    let mut simulation = Simulation::new(config());
+    for tick in 0..240 {
+        simulation.tick(&input_for(tick));
+    }
+
+    let manifest = AssetManifest::load(ASSET_MANIFEST_PATH).expect("Asset manifest should load");
+    let text = std::fs::read_to_string(manifest.data(assets::ATLASES).unwrap()).unwrap();
+    let renamed = text
+        .replace("\"ship_", "\"hull_")
+        .replace("\"asteroid_", "\"rock_")
+        .replace("\"asteroid\": (", "\"rock\": (");
+    assert_ne!(renamed, text);
+    let path =
+        std::env::temp_dir().join(format!("asteroidgame-{}-atlases.ron", std::process::id()));
+    std::fs::write(&path, renamed).unwrap();
+    let mut definitions = config().definitions();
+    definitions.atlases =
+        Atlases::load(path.to_str().unwrap(), &manifest).expect("Renamed atlases should load");
+    std::fs::remove_file(&path).unwrap();
+    // Asteroids in play still name the old atlas in their animations
+    for tier in definitions.asteroid_archetypes.tiers.iter_mut() {
+        tier.atlas = String::from("rock");
+    }
+    simulation.reload_definitions(&definitions);
+    assert!(simulation.config().atlases.atlases.contains_key("rock"));
+    simulation.tick(&input_for(240));
+
+    let world = simulation.world();
+    let atlases = world.read_resource::<Atlases>();
+    let renderables = world.read_storage::<Renderable>();
+    let animations = world.read_storage::<Animation>();
+    assert!(renderables.join().count() > 2);
+    for renderable in renderables.join() {
+        assert!(
+            atlases
+                .sprite(&renderable.atlas, &renderable.sprite)
+                .is_some(),
+            "{}/{} is gone",
+            renderable.atlas,
+            renderable.sprite
+        );
+    }
+    for animation in animations.join() {
+        assert!(atlases.clip(&animation.set, &animation.clip).is_some());
+    }
+}
@@ -0,0 +1,64 @@
+use asteroidgame::input::{Action, Aim};
+use asteroidgame::replay::{self, Replay, ReplayRecorder};
+use asteroidgame::simulation::{InputSnapshot, Simulation, SimulationConfig};
+use asteroidgame::vector2D::Vector2D;
+
+fn config(seed: u64) -> SimulationConfig {
+    let mut config = SimulationConfig::load().expect("Data files should load");
+    config.seed = seed;
+    config
+}
+
+// Plays a short session the way the front-end would, aiming at a point that
+// moves now and then and firing in bursts.
+fn record(seed: u64, ticks: u32) -> (Replay, Simulation) {
+    let mut simulation = Simulation::new(config(seed));
+    let mut recorder = ReplayRecorder::new(&simulation);
+    for tick in 0..ticks {
+        let mut input = InputSnapshot {
+            aim: Aim::At(Vector2D::new(400.0 + (tick / 30) as f64 * 7.0, 100.0)),
+            ..InputSnapshot::default()
+        };
+        if tick % 40 < 20 {
+            input.actions.insert(Action::Fire);
+        }
+        if tick % 120 < 60 {
+            input.actions.insert(Action::MoveRight);
+        }
+        simulation.tick(&input);
+        recorder.record(&input, &simulation);
+    }
+    (recorder.finish(&simulation), simulation)
+}
+
+#[test]
+fn replay_reproduces_the_recorded_run() {
+    let (recorded, original) = record(7, 1000);
+    assert_eq!(recorded.ticks(), 1000);
+
+    let loaded = Replay::from_ron(&recorded.to_ron().unwrap()).unwrap();
+    let replayed = replay::play_headless(&loaded, config(0)).unwrap();
+    assert_eq!(
+        replay::checksum(replayed.world()),
+        replay::checksum(original.world())
+    );
+}
+
+#[test]
+fn replay_with_the_wrong_seed_is_a_desync() {
+    let (mut recorded, _) = record(7, 200);
+    recorded.seed = 8;
+    let error = match replay::play_headless(&recorded, config(0)) {
+        Ok(_) => panic!("Replay with the wrong seed played through"),
+        Err(error) => error,
+    };
+    assert!(error.contains("desynced"), "{}", error);
+}
+
+#[test]
+fn held_input_is_stored_once_per_change() {
+    let (recorded, _) = record(7, 600);
+    let text = recorded.to_ron().unwrap();
+    // One entry per input change rather than one per tick.
+    assert!(text.matches("ticks:").count() < 100);
+}
@@ -0,0 +1,100 @@
+use asteroidgame::archetypes::ROCK;
+use asteroidgame::components::{Asteroid, GameData, Player, Position};
+use asteroidgame::prefab::{self, Prefab};
+use asteroidgame::responses::SoundCue;
+use asteroidgame::simulation::{InputSnapshot, Simulation, SimulationConfig};
+use specs::shrev::EventChannel;
+use specs::{Join, WorldExt};
+
+fn simulation() -> Simulation {
+    Simulation::new(SimulationConfig::load().expect("Data files should load"))
+}
+
+// Drops a small asteroid right onto the ship, so it is lost next tick.
+fn crash_ship(simulation: &mut Simulation) {
+    let ship = {
+        let world = simulation.world();
+        let positions = world.read_storage::<Position>();
+        let players = world.read_storage::<Player>();
+        (&positions, &players).join().next().unwrap().0.clone()
+    };
+    prefab::spawn(
+        simulation.world_mut(),
+        &prefab::asteroid_name(ROCK, 1),
+        Prefab {
+            position: Some(ship),
+            ..Prefab::default()
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn losing_the_ship_asks_for_its_sound() {
+    let mut simulation = simulation();
+    let mut reader = simulation
+        .world_mut()
+        .write_resource::<EventChannel<SoundCue>>()
+        .register_reader();
+    crash_ship(&mut simulation);
+
+    simulation.tick(&InputSnapshot::default());
+    let world = simulation.world();
+    let channel = world.read_resource::<EventChannel<SoundCue>>();
+    let cues: Vec<_> = channel.read(&mut reader).copied().collect();
+    assert_eq!(cues, vec![SoundCue::ShipDestroyed]);
+}
+
+#[test]
+fn extra_life_earned_as_the_last_ship_is_lost_brings_another() {
+    let mut simulation = simulation();
+    let first_extra_life = simulation.config().lives.extra_life_score(0).unwrap();
+    for gamedata in (&mut simulation.world_mut().write_storage::<GameData>()).join() {
+        gamedata.lives = 1;
+        gamedata.score = first_extra_life;
+    }
+    crash_ship(&mut simulation);
+
+    simulation.tick(&InputSnapshot::default());
+    assert!(simulation.world().read_storage::<Player>().is_empty());
+    assert!(!simulation.is_game_over());
+    let gamedatas = simulation.world().read_storage::<GameData>();
+    let gamedata = gamedatas.join().next().unwrap();
+    assert_eq!(gamedata.lives, 1);
+    assert!(gamedata.respawn_timer.is_some());
+}
+
+#[test]
+fn waves_ask_for_sounds_when_they_start_and_are_cleared() {
+    let mut simulation = simulation();
+    let mut reader = simulation
+        .world_mut()
+        .write_resource::<EventChannel<SoundCue>>()
+        .register_reader();
+    let mut cues = Vec::new();
+    let mut tick = |simulation: &mut Simulation, cues: &mut Vec<SoundCue>| {
+        simulation.tick(&InputSnapshot::default());
+        let channel = simulation.world().read_resource::<EventChannel<SoundCue>>();
+        cues.extend(channel.read(&mut reader).copied());
+    };
+
+    let countdown = simulation.config().waves.countdown;
+    let ticks = (countdown * simulation.tick_rate() as f64) as u32 + 2;
+    for _ in 0..ticks {
+        tick(&mut simulation, &mut cues);
+    }
+    assert_eq!(cues, vec![SoundCue::WaveStarted { wave: 1 }]);
+
+    // Clearing the field by hand ends the wave
+    {
+        let world = simulation.world_mut();
+        let asteroids: Vec<_> = (&world.entities(), &world.read_storage::<Asteroid>())
+            .join()
+            .map(|(entity, _)| entity)
+            .collect();
+        world.delete_entities(&asteroids).unwrap();
+    }
+    tick(&mut simulation, &mut cues);
+    tick(&mut simulation, &mut cues);
+    assert_eq!(cues.last(), Some(&SoundCue::WaveCleared { wave: 1 }));
+}
@@ -0,0 +1,187 @@
+use asteroidgame::animation::SPIN;
+use asteroidgame::archetypes::ROCK;
+use asteroidgame::atlas::Atlases;
+use asteroidgame::components::{Animation, Asteroid, Player, RenderLayer, Renderable};
+use asteroidgame::input::Action;
+use asteroidgame::save::SAVE_VERSION;
+use asteroidgame::simulation::{InputSnapshot, Simulation, SimulationConfig};
+use specs::{Join, WorldExt};
+
+fn simulation() -> Simulation {
+    Simulation::new(SimulationConfig::load().expect("Data files should load"))
+}
+
+// Flies in a square while firing, so asteroids split and random numbers are
+// drawn after the save.
+fn input_for(tick: u32) -> InputSnapshot {
+    let mut input = InputSnapshot::default();
+    if tick.is_multiple_of(20) {
+        input.actions.insert(Action::Fire);
+    }
+    match (tick / 45) % 4 {
+        0 => input.actions.insert(Action::MoveRight),
+        1 => input.actions.insert(Action::MoveDown),
+        2 => input.actions.insert(Action::MoveLeft),
+        _ => input.actions.insert(Action::MoveUp),
+    }
+    input
+}
+
+fn run(simulation: &mut Simulation, ticks: std::ops::Range<u32>) {
+    for tick in ticks {
+        simulation.tick(&input_for(tick));
+    }
+}
+
+fn entity_count(simulation: &Simulation) -> usize {
+    simulation.world().entities().join().count()
+}
+
+#[test]
+fn restored_world_ticks_like_the_original() {
+    let mut original = simulation();
+    // Long enough for the first wave to be in play.
+    run(&mut original, 0..240);
+    let save = original.save().unwrap();
+
+    let mut restored = simulation();
+    restored.restore(&save).unwrap();
+    assert_eq!(restored.save().unwrap(), save);
+    assert!(entity_count(&restored) > 2);
+
+    run(&mut original, 240..420);
+    run(&mut restored, 240..420);
+    assert_eq!(restored.save().unwrap(), original.save().unwrap());
+}
+
+#[test]
+fn save_from_another_version_is_rejected() {
+    let mut original = simulation();
+    run(&mut original, 0..10);
+    let save = original.save().unwrap();
+    let version = format!("version: {},", SAVE_VERSION);
+    assert!(save.contains(&version));
+
+    let mut other = simulation();
+    let before = other.save().unwrap();
+    let newer = save.replacen(&version, "version: 99,", 1);
+    assert!(other.restore(&newer).unwrap_err().contains("newer"));
+    let older = save.replacen(&version, "version: 0,", 1);
+    assert!(other
+        .restore(&older)
+        .unwrap_err()
+        .contains("no longer supported"));
+    assert_eq!(other.save().unwrap(), before);
+}
+
+#[test]
+fn broken_save_leaves_the_world_alone() {
+    let mut simulation = simulation();
+    let before = simulation.save().unwrap();
+    assert!(simulation.restore("(version: 1, entities: [").is_err());
+    assert!(simulation.restore("").is_err());
+    assert_eq!(simulation.save().unwrap(), before);
+}
+
+// Saves written by older versions of the game, to check they still restore.
+const SAVE_V1: &str = include_str!("fixtures/save_v1.ron");
+const SAVE_V2: &str = include_str!("fixtures/save_v2.ron");
+const SAVE_V3: &str = include_str!("fixtures/save_v3.ron");
+const SAVE_V4: &str = include_str!("fixtures/save_v4.ron");
+const SAVE_V5: &str = include_str!("fixtures/save_v5.ron");
+
+fn restored(save: &str) -> Simulation {
+    let mut simulation = simulation();
+    simulation.restore(save).unwrap();
+    simulation
+}
+
+#[test]
+fn saves_without_a_random_number_generator_get_a_fresh_one() {
+    assert!(!SAVE_V1.contains("rng:"));
+    let mut first = restored(SAVE_V1);
+    let second = restored(SAVE_V1);
+    assert!(entity_count(&first) > 2);
+    assert!(first.save().unwrap().contains("rng: Some("));
+    assert_ne!(first.save().unwrap(), second.save().unwrap());
+
+    run(&mut first, 240..420);
+    assert!(entity_count(&first) > 0);
+}
+
+#[test]
+fn saves_without_render_layers_get_them_worked_out() {
+    for save in [SAVE_V1, SAVE_V2] {
+        assert!(!save.contains("layer:"));
+        let restored = restored(save);
+        let world = restored.world();
+        let renderables = world.read_storage::<Renderable>();
+        let players = world.read_storage::<Player>();
+        let asteroids = world.read_storage::<Asteroid>();
+        assert!((&renderables, &players).join().count() > 0);
+        for (renderable, _) in (&renderables, &players).join() {
+            assert_eq!(renderable.layer, RenderLayer::Player);
+        }
+        assert!((&renderables, &asteroids).join().count() > 0);
+        for (renderable, _) in (&renderables, &asteroids).join() {
+            assert_eq!(renderable.layer, RenderLayer::Asteroids);
+        }
+    }
+}
+
+#[test]
+fn saves_without_animations_get_them_from_the_prefabs() {
+    for save in [SAVE_V1, SAVE_V2, SAVE_V3] {
+        assert!(!save.contains("clip:"));
+        let restored = restored(save);
+        let world = restored.world();
+        let animations = world.read_storage::<Animation>();
+        let players = world.read_storage::<Player>();
+        let asteroids = world.read_storage::<Asteroid>();
+        assert!(players.join().count() > 0);
+        assert_eq!(
+            (&animations, &players).join().count(),
+            players.join().count()
+        );
+        assert!(asteroids.join().count() > 0);
+        for (animation, _) in (&animations, &asteroids).join() {
+            assert_eq!(animation.clip, SPIN);
+        }
+        assert_eq!(
+            (&animations, &asteroids).join().count(),
+            asteroids.join().count()
+        );
+    }
+}
+
+#[test]
+fn saves_without_sprite_names_get_them_worked_out() {
+    for save in [SAVE_V1, SAVE_V2, SAVE_V3, SAVE_V4] {
+        assert!(save.contains("texture_name:"));
+        assert!(!save.contains("sprite:"));
+        let restored = restored(save);
+        let world = restored.world();
+        let atlases = world.read_resource::<Atlases>();
+        let renderables = world.read_storage::<Renderable>();
+        assert!(renderables.join().count() > 1);
+        for renderable in renderables.join() {
+            assert!(atlases
+                .sprite(&renderable.atlas, &renderable.sprite)
+                .is_some());
+        }
+    }
+}
+
+#[test]
+fn saves_without_asteroid_kinds_only_have_rocks() {
+    for save in [SAVE_V1, SAVE_V2, SAVE_V3, SAVE_V4, SAVE_V5] {
+        assert!(!save.contains("kind:"));
+        let restored = restored(save);
+        let world = restored.world();
+        let asteroids = world.read_storage::<Asteroid>();
+        assert!(asteroids.join().count() > 0);
+        for asteroid in asteroids.join() {
+            assert_eq!(asteroid.kind, ROCK);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use asteroidgame::input::Action;
+use asteroidgame::simulation::{InputSnapshot, Simulation, SimulationConfig};
+
+fn simulation(seed: u64) -> Simulation {
+    let mut config = SimulationConfig::load().expect("Data files should load");
+    config.seed = seed;
+    Simulation::new(config)
+}
+
+// Fires while moving about, so waves are spawned and asteroids split.
+fn play(simulation: &mut Simulation) {
+    for tick in 0..900u32 {
+        let mut input = InputSnapshot::default();
+        if tick.is_multiple_of(10) {
+            input.actions.insert(Action::Fire);
+        }
+        if tick % 100 < 50 {
+            input.actions.insert(Action::MoveLeft);
+        }
+        simulation.tick(&input);
+    }
+}
+
+#[test]
+fn same_seed_and_input_play_out_the_same() {
+    let mut first = simulation(42);
+    let mut second = simulation(42);
+    play(&mut first);
+    play(&mut second);
+    assert_eq!(first.save().unwrap(), second.save().unwrap());
+}
+
+#[test]
+fn restart_uses_the_new_seed() {
+    let mut first = simulation(1);
+    first.restart(42);
+    let mut second = simulation(2);
+    second.restart(42);
+    play(&mut first);
+    play(&mut second);
+    assert_eq!(first.save().unwrap(), second.save().unwrap());
+
+    let mut other = simulation(3);
+    play(&mut other);
+    assert_ne!(first.save().unwrap(), other.save().unwrap());
+}
@@ -0,0 +1,95 @@
+use asteroidgame::animation::PlayMode;
+use asteroidgame::atlas::SpriteRect;
+use asteroidgame::sheet::Sheet;
+use asteroidgame::simulation::SimulationConfig;
+
+// The way TexturePacker writes a JSON hash, without any tags.
+const TEXTURE_PACKER: &str = r#"{
+    "frames": {
+        "walk_1.png": {
+            "frame": {"x": 0, "y": 0, "w": 20, "h": 30},
+            "rotated": false,
+            "trimmed": false,
+            "spriteSourceSize": {"x": 0, "y": 0, "w": 20, "h": 30},
+            "sourceSize": {"w": 20, "h": 30}
+        }
+    },
+    "meta": {"image": "walk.png", "size": {"w": 20, "h": 30}, "scale": "1"}
+}"#;
+
+// The way Aseprite writes one, frames listed out of name order.
+const ASEPRITE: &str = r#"{
+    "frames": {
+        "b": {"frame": {"x": 0, "y": 0, "w": 10, "h": 10}, "duration": 100},
+        "a": {"frame": {"x": 10, "y": 0, "w": 10, "h": 10}, "duration": 200},
+        "c": {"frame": {"x": 20, "y": 0, "w": 10, "h": 10}, "duration": 100}
+    },
+    "meta": {
+        "frameTags": [
+            {"name": "run", "from": 0, "to": 2, "direction": "forward"},
+            {"name": "back", "from": 1, "to": 2, "direction": "reverse", "repeat": "1"},
+            {"name": "sway", "from": 0, "to": 1, "direction": "pingpong"}
+        ]
+    }
+}"#;
+
+#[test]
+fn texture_packer_frames_become_sprites() {
+    let sheet = Sheet::from_json(TEXTURE_PACKER).unwrap();
+    assert_eq!(
+        sheet.sprites["walk_1.png"],
+        SpriteRect {
+            x: 0,
+            y: 0,
+            width: 20,
+            height: 30
+        }
+    );
+    assert!(sheet.clips.is_empty());
+}
+
+#[test]
+fn aseprite_tags_count_frames_in_file_order() {
+    let sheet = Sheet::from_json(ASEPRITE).unwrap();
+    let sprites = |clip: &str| -> Vec<(String, f64)> {
+        sheet.clips[clip]
+            .frames
+            .iter()
+            .map(|frame| (frame.sprite.clone(), frame.duration))
+            .collect()
+    };
+    assert_eq!(
+        sprites("run"),
+        [
+            ("b".to_string(), 0.1),
+            ("a".to_string(), 0.2),
+            ("c".to_string(), 0.1)
+        ]
+    );
+    assert_eq!(sheet.clips["run"].mode, PlayMode::Loop);
+    assert_eq!(
+        sprites("back"),
+        [("c".to_string(), 0.1), ("a".to_string(), 0.2)]
+    );
+    assert_eq!(sheet.clips["back"].mode, PlayMode::Once);
+    assert_eq!(sheet.clips["sway"].mode, PlayMode::PingPong);
+}
+
+#[test]
+fn rotated_frames_and_tags_past_the_end_are_rejected() {
+    let rotated = TEXTURE_PACKER.replace(r#""rotated": false"#, r#""rotated": true"#);
+    assert!(Sheet::from_json(&rotated).unwrap_err().contains("rotated"));
+    let past_end = ASEPRITE.replace(
+        r#""to": 2, "direction": "forward""#,
+        r#""to": 3, "direction": "forward""#,
+    );
+    assert_ne!(past_end, ASEPRITE);
+    assert!(Sheet::from_json(&past_end).unwrap_err().contains("run"));
+}
+
+#[test]
+fn rebel_atlas_comes_from_its_sheet() {
+    let config = SimulationConfig::load().expect("Data files should load");
+    assert!(config.atlases.sprite("rebel", "rebel_0").is_some());
+    assert!(config.atlases.clip("rebel", "idle").is_some());
+}
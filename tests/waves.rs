@@ -0,0 +1,95 @@
+use asteroidgame::archetypes::ROCK;
+use asteroidgame::assets::{self, AssetManifest, ASSET_MANIFEST_PATH};
+use asteroidgame::boundary::PlayField;
+use asteroidgame::components::GameRng;
+use asteroidgame::simulation::SimulationConfig;
+use asteroidgame::vector2D::Vector2D;
+use asteroidgame::waves::{self, WaveConfig};
+use rand::SeedableRng;
+
+use std::collections::BTreeSet;
+
+fn waves_text() -> String {
+    let manifest = AssetManifest::load(ASSET_MANIFEST_PATH).expect("Asset manifest should load");
+    std::fs::read_to_string(manifest.data(assets::WAVES).unwrap())
+        .expect("Waves file should be readable")
+}
+
+#[test]
+fn shipped_waves_load() {
+    assert!(WaveConfig::from_ron(&waves_text()).is_ok());
+}
+
+#[test]
+fn negative_safe_radius_is_rejected() {
+    let text = waves_text();
+    let negative = text.replacen("safe_radius: 150.0", "safe_radius: -150.0", 1);
+    assert_ne!(negative, text);
+    assert!(WaveConfig::from_ron(&negative)
+        .unwrap_err()
+        .contains("safe_radius"));
+}
+
+#[test]
+fn later_waves_mix_enemy_types() {
+    let config = SimulationConfig::load().expect("Data files should load");
+    let mut rng = GameRng::seed_from_u64(7);
+    let wave = config.waves.wave(5);
+    let mut kinds = BTreeSet::new();
+    for _ in 0..200 {
+        let entry = wave.random_size(&mut rng);
+        let tier = config
+            .asteroid_archetypes
+            .tier(&entry.kind, entry.size)
+            .expect("Waves should only pick known tiers");
+        kinds.insert(tier.kind.clone());
+    }
+    assert!(kinds.contains(ROCK));
+    assert!(kinds.len() > 1);
+
+    let first = config.waves.wave(1);
+    assert!(first.sizes.iter().all(|entry| entry.kind == ROCK));
+}
+
+#[test]
+fn unknown_enemy_type_is_rejected() {
+    let config = SimulationConfig::load().expect("Data files should load");
+    let text = waves_text();
+    let unknown = text.replacen("kind: \"rock\"", "kind: \"comet\"", 1);
+    assert_ne!(unknown, text);
+    let waves = WaveConfig::from_ron(&unknown).unwrap();
+    assert!(waves
+        .check_sizes(&config.asteroid_archetypes)
+        .unwrap_err()
+        .contains("comet"));
+}
+
+// Distance between two points on the field, the short way round its edges.
+fn field_distance(field: &PlayField, a: Vector2D, b: Vector2D) -> f64 {
+    let across = |d: f64, size: u32| {
+        let size = size as f64;
+        (d + size / 2.0).rem_euclid(size) - size / 2.0
+    };
+    Vector2D::new(
+        across(a.x - b.x, field.width),
+        across(a.y - b.y, field.height),
+    )
+    .magnitude()
+}
+
+#[test]
+fn safe_spot_without_room_is_on_the_edge() {
+    let field = PlayField {
+        width: 800,
+        height: 600,
+    };
+    let avoid = field.center();
+    let mut rng = GameRng::seed_from_u64(7);
+    for safe_radius in [300.0, 1000.0] {
+        let spot = waves::safe_spot(&field, avoid, safe_radius, &mut rng);
+        assert!((field_distance(&field, spot, avoid) - 300.0).abs() < 1e-6);
+    }
+    let spot = waves::safe_spot(&field, avoid, 150.0, &mut rng);
+    let distance = field_distance(&field, spot, avoid);
+    assert!((150.0..=300.0).contains(&distance));
+}